@@ -0,0 +1,371 @@
+// Benchmarks of the planners and of whole games on generated labyrinths.
+//
+//     cargo bench --bench planners -- --save main.txt       # run and save the results
+//     cargo bench --bench planners -- --compare main.txt    # run and compare with saved results
+//     cargo bench --bench planners -- compute_path          # only benches whose name contains the filter
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env, fs, process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::exploration;
+use the_labyrinth::game_sample::GameSample;
+use the_labyrinth::generator::Labyrinth;
+use the_labyrinth::solver::Solver;
+use the_labyrinth::vect2_t::UCoord2;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const SIZES: [(&str, usize, usize); 3] =
+    [("small", 21, 11), ("medium", 61, 31), ("large", 301, 151)];
+
+// Timings and allocations of every call of one bench.
+struct Measure {
+    name: String,
+    durations: Vec<Duration>,
+    allocations: usize,
+    allocated_bytes: usize,
+}
+
+impl Measure {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            durations: Vec::new(),
+            allocations: 0,
+            allocated_bytes: 0,
+        }
+    }
+
+    fn record<T, F: FnOnce() -> T>(&mut self, f: F) -> T {
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let result = f();
+        self.durations.push(start.elapsed());
+        self.allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+        self.allocated_bytes += ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes;
+        result
+    }
+
+    fn result(mut self) -> BenchResult {
+        self.durations.sort();
+        let calls = self.durations.len().max(1);
+        let micros = |d: &Duration| d.as_secs_f64() * 1e6;
+        let durations = &self.durations;
+        let percentile = |p: usize| {
+            durations
+                .get((durations.len() * p / 100).min(durations.len().saturating_sub(1)))
+                .map_or(0., micros)
+        };
+        BenchResult {
+            name: self.name.clone(),
+            calls: durations.len(),
+            mean_us: durations.iter().map(micros).sum::<f64>() / calls as f64,
+            median_us: percentile(50),
+            p95_us: percentile(95),
+            max_us: durations.last().map_or(0., micros),
+            allocs_per_call: self.allocations as f64 / calls as f64,
+            bytes_per_call: self.allocated_bytes as f64 / calls as f64,
+        }
+    }
+}
+
+struct BenchResult {
+    name: String,
+    calls: usize,
+    mean_us: f64,
+    median_us: f64,
+    p95_us: f64,
+    max_us: f64,
+    allocs_per_call: f64,
+    bytes_per_call: f64,
+}
+
+const HEADER: &str = "# name calls mean_us median_us p95_us max_us allocs_per_call bytes_per_call";
+
+impl BenchResult {
+    fn to_line(&self) -> String {
+        format!(
+            "{} {} {:.2} {:.2} {:.2} {:.2} {:.1} {:.0}",
+            self.name,
+            self.calls,
+            self.mean_us,
+            self.median_us,
+            self.p95_us,
+            self.max_us,
+            self.allocs_per_call,
+            self.bytes_per_call
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields.len() != 8 || line.starts_with('#') {
+            return None;
+        }
+        let number = |i: usize| fields[i].parse::<f64>().ok();
+        Some(Self {
+            name: fields[0].to_owned(),
+            calls: fields[1].parse().ok()?,
+            mean_us: number(2)?,
+            median_us: number(3)?,
+            p95_us: number(4)?,
+            max_us: number(5)?,
+            allocs_per_call: number(6)?,
+            bytes_per_call: number(7)?,
+        })
+    }
+}
+
+fn free_squares(board: &Board) -> Vec<UCoord2> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| UCoord2::new(x, y)))
+        .filter(|coord| board.get_content(coord) == Content::Empty)
+        .collect()
+}
+
+fn bench_update_with(size: &str, labyrinth: &Labyrinth) -> BenchResult {
+    let rows = labyrinth.rows_as_strings();
+    let mut measure = Measure::new(format!("update_with/{}", size));
+
+    // Every free square visited once on a fresh board, as Kirk would discover the map.
+    let squares = free_squares(&Board::from_rows(&rows, labyrinth.rounds));
+    let mut board = Board::new(labyrinth.width(), labyrinth.height(), labyrinth.rounds);
+    for &coord in &squares {
+        measure.record(|| board.update_with(coord, &rows));
+    }
+
+    measure.result()
+}
+
+fn bench_compute_path(size: &str, labyrinth: &Labyrinth, rng: &mut StdRng) -> BenchResult {
+    let board = Board::from_rows(&labyrinth.rows_as_strings(), labyrinth.rounds);
+    let squares = free_squares(&board);
+    let mut a_star_algo = AStarAlgo::new();
+    let mut measure = Measure::new(format!("compute_path/{}", size));
+
+    for _ in 0..50 {
+        let start = *squares.choose(rng).unwrap();
+        let target = *squares.choose(rng).unwrap();
+        if start != target {
+            measure.record(|| a_star_algo.compute_path(&board, start, target, &[Content::Empty]));
+        }
+    }
+
+    measure.result()
+}
+
+fn bench_start_look_forward(size: &str, labyrinth: &Labyrinth, rng: &mut StdRng) -> BenchResult {
+    let board = Board::from_rows(&labyrinth.rows_as_strings(), labyrinth.rounds);
+    let squares = free_squares(&board);
+    let mut measure = Measure::new(format!("start_look_forward/{}", size));
+
+    for _ in 0..50 {
+        let start = *squares.choose(rng).unwrap();
+        let mut engine = exploration::Engine::new();
+        measure.record(|| engine.start_look_forward(&board, start, &Some(labyrinth.cmd_room), 100));
+    }
+
+    measure.result()
+}
+
+// The frontier of a half known board, from the squares or from the bitboards.
+fn bench_frontier_squares(size: &str, labyrinth: &Labyrinth, bitboard: bool) -> BenchResult {
+    let rows: Vec<String> = labyrinth
+        .rows_as_strings()
+        .iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .map(|(x, square)| if (x + y) % 3 == 0 { '?' } else { square })
+                .collect()
+        })
+        .collect();
+    let mut board = Board::from_rows(&rows, labyrinth.rounds);
+    board.set_bitboard(bitboard);
+    let kind = if bitboard { "bits" } else { "squares" };
+    let mut measure = Measure::new(format!("frontier_{}/{}", kind, size));
+
+    for _ in 0..50 {
+        measure.record(|| exploration::frontier_squares(&board));
+    }
+
+    measure.result()
+}
+
+// A whole game through the GameSample protocol, each turn of the solver is one call, with or without the bitboards.
+fn bench_game_turn(size: &str, labyrinth: &Labyrinth, bitboard: bool) -> BenchResult {
+    let mut game_sample = GameSample::from_sample_string(&labyrinth.to_sample_string()).unwrap();
+    let mut solver = Solver::new(labyrinth.width(), labyrinth.height(), labyrinth.rounds);
+    solver.set_bitboard(bitboard);
+    let kind = if bitboard { "bits" } else { "squares" };
+    let mut measure = Measure::new(format!("game_turn_{}/{}", kind, size));
+
+    let mut input_board_buffer = vec![String::new(); labyrinth.height()];
+
+    while game_sample.outcome().is_none() {
+        let mut stdin = game_sample.round_data_reader();
+        let mut input_first_line = String::new();
+        stdin.read_line(&mut input_first_line).unwrap();
+        let inputs: Vec<usize> = input_first_line
+            .split_whitespace()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        for row in input_board_buffer.iter_mut() {
+            row.clear();
+            stdin.read_line(row).unwrap();
+        }
+
+        let rick_coord = (inputs[1], inputs[0]).into();
+        match measure.record(|| solver.next_move(rick_coord, &input_board_buffer)) {
+            Some(dir) => game_sample.set_action(dir.label()),
+            None => game_sample.stop(),
+        }
+    }
+
+    measure.result()
+}
+
+fn main() {
+    let mut filter = None;
+    let mut save = None;
+    let mut compare = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = args.next(),
+            "--compare" => compare = args.next(),
+            "--bench" => (),
+            _ if arg.starts_with("--") => {
+                eprintln!("unknown option {}", arg);
+                process::exit(2);
+            }
+            _ => filter = Some(arg),
+        }
+    }
+
+    let baseline: Vec<BenchResult> = compare
+        .map(|filename| {
+            fs::read_to_string(&filename)
+                .unwrap_or_else(|err| panic!("cannot read {}: {}", filename, err))
+                .lines()
+                .filter_map(BenchResult::from_line)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut results = Vec::new();
+    let selected = |name: &str| filter.as_ref().is_none_or(|f| name.contains(f.as_str()));
+
+    for &(size, width, height) in SIZES.iter() {
+        let mut rng = StdRng::seed_from_u64(2020);
+        let labyrinth = Labyrinth::generate(&mut rng, width, height, 0.1);
+
+        if selected(&format!("update_with/{}", size)) {
+            results.push(bench_update_with(size, &labyrinth));
+        }
+        if selected(&format!("compute_path/{}", size)) {
+            results.push(bench_compute_path(size, &labyrinth, &mut rng));
+        }
+        if selected(&format!("start_look_forward/{}", size)) {
+            results.push(bench_start_look_forward(size, &labyrinth, &mut rng));
+        }
+        for &bitboard in &[false, true] {
+            let kind = if bitboard { "bits" } else { "squares" };
+            if selected(&format!("frontier_{}/{}", kind, size)) {
+                results.push(bench_frontier_squares(size, &labyrinth, bitboard));
+            }
+        }
+        for &bitboard in &[false, true] {
+            let kind = if bitboard { "bits" } else { "squares" };
+            if selected(&format!("game_turn_{}/{}", kind, size)) {
+                results.push(bench_game_turn(size, &labyrinth, bitboard));
+            }
+        }
+    }
+
+    println!(
+        "{:<28} {:>6} {:>10} {:>10} {:>10} {:>10} {:>8} {:>10}",
+        "name", "calls", "mean us", "median us", "p95 us", "max us", "allocs", "bytes"
+    );
+    for result in &results {
+        println!(
+            "{:<28} {:>6} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>8.1} {:>10.0}",
+            result.name,
+            result.calls,
+            result.mean_us,
+            result.median_us,
+            result.p95_us,
+            result.max_us,
+            result.allocs_per_call,
+            result.bytes_per_call
+        );
+        if let Some(base) = baseline.iter().find(|base| base.name == result.name) {
+            println!(
+                "{:<28} {:>6} {:>+9.1}% {:>+9.1}% {:>+9.1}% {:>+9.1}% {:>+7.1}% {:>+9.1}%",
+                "  vs baseline",
+                "",
+                change(base.mean_us, result.mean_us),
+                change(base.median_us, result.median_us),
+                change(base.p95_us, result.p95_us),
+                change(base.max_us, result.max_us),
+                change(base.allocs_per_call, result.allocs_per_call),
+                change(base.bytes_per_call, result.bytes_per_call)
+            );
+        }
+    }
+
+    if let Some(filename) = save {
+        let mut content = String::from(HEADER);
+        content.push('\n');
+        for result in &results {
+            content.push_str(&result.to_line());
+            content.push('\n');
+        }
+        fs::write(&filename, content)
+            .unwrap_or_else(|err| panic!("cannot write {}: {}", filename, err));
+        println!("results saved in {}", filename);
+    }
+}
+
+fn change(base: f64, new: f64) -> f64 {
+    if base == 0. {
+        0.
+    } else {
+        (new - base) / base * 100.
+    }
+}
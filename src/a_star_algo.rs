@@ -1,63 +1,110 @@
 use std::{
-    collections::HashMap,
-    iter
+    cmp::Reverse,
+    collections::BinaryHeap,
+    iter,
+    sync::Arc,
 };
 
 use crate::vect2_t::UCoord2;
 use crate::direction::Direction;
 use crate::board::{Board,Content};
+use crate::grid::Grid;
+use crate::keys::KeySet;
+use crate::movement::Movement;
+use crate::topology::Topology;
 
+// A square with the keys held and the turn when reaching it : the same square is a different state with more keys,
+// or at another turn while guards move.
+type State = (UCoord2, KeySet, usize);
 
+// Best move cost found for a state and the state it comes from, closed once it left the open list.
+#[derive(Clone)]
 struct Node {
-    coord: UCoord2,
+    keys: KeySet,
+    turn: usize,
     move_cost: i32,
-    heuristic: i32,
-    parent_coord: UCoord2,
+    parent_state: State,
+    closed: bool,
 }
 
-impl Node {
-    fn new(coord: UCoord2, move_cost: i32, heuristic: i32, parent_coord: UCoord2) -> Self {
-        Self {
-            coord,
-            move_cost,
-            heuristic,
-            parent_coord,
-        }
-    }
+// Entry of the open list : cost, heuristic, then the square, so that ties are broken on the square and a game always
+// plays the same. An entry whose move cost was improved since it was pushed is skipped.
+type OpenEntry = Reverse<(i32, i32, usize, usize, KeySet, usize, i32)>;
 
-    fn cost(&self) -> i32 {
-        self.move_cost + self.heuristic
-    }
+pub struct AStarAlgo {
+    open_list: BinaryHeap<OpenEntry>,
+    // the states of each square, a single one without keys nor guards
+    nodes: Grid<Vec<Node>>,
+    // squares with states, cleared before the next search rather than the whole grid
+    reached_coords: Vec<UCoord2>,
+    // known portals with a known exit, for the heuristic
+    portal_coords: Vec<UCoord2>,
+    // topology of the board, for the heuristic
+    topology: Arc<dyn Topology>,
 }
 
-pub struct AStarAlgo {
-    open_list: HashMap<UCoord2, Node>,
-    closed_list: HashMap<UCoord2, Node>,
+impl Default for AStarAlgo {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AStarAlgo {
     pub fn new() -> Self {
         Self {
-            open_list: HashMap::new(),
-            closed_list: HashMap::new(),
+            open_list: BinaryHeap::new(),
+            nodes: Grid::new(0, 0, Vec::new()),
+            reached_coords: Vec::new(),
+            portal_coords: Vec::new(),
+            topology: Arc::new(Movement::orthogonal()),
         }
     }
 
-    fn clear(&mut self) {
+    fn clear(&mut self, width: usize, height: usize) {
         self.open_list.clear();
-        self.closed_list.clear();
+        if self.nodes.width() != width || self.nodes.height() != height {
+            self.nodes = Grid::new(width, height, Vec::new());
+            self.reached_coords.clear();
+        }
+        for coord in self.reached_coords.drain(..) {
+            self.nodes[coord].clear();
+        }
+    }
+
+    fn push_node(&mut self, coord: UCoord2, node: Node) {
+        if self.nodes[coord].is_empty() {
+            self.reached_coords.push(coord);
+        }
+        self.nodes[coord].push(node);
+    }
+
+    fn node(&self, (coord, keys, turn): State) -> Option<&Node> {
+        self.nodes[coord].iter().find(|node| node.keys == keys && node.turn == turn)
     }
 
-    fn heuristic(&self, coord: UCoord2, target_coord: UCoord2) -> i32 {
-        let v = target_coord - coord;
-        v.x.abs() + v.y.abs()
-        // (v.x.abs() + v.y.abs()) * 2
+    fn node_mut(&mut self, (coord, keys, turn): State) -> Option<&mut Node> {
+        self.nodes[coord].iter_mut().find(|node| node.keys == keys && node.turn == turn)
+    }
+
+    // Distance of the topology, Manhattan for the four moves : never overestimates as long as a move costs at least 1.
+    // A path through portals walks at least to the nearest portal, then from the portal nearest to the target.
+    pub fn heuristic(&self, coord: UCoord2, target_coord: UCoord2) -> i32 {
+        let distance = |from: UCoord2, to: UCoord2| self.topology.distance(from, to);
+        let direct = distance(coord, target_coord);
+
+        let to_portal = self.portal_coords.iter().map(|&portal| distance(coord, portal)).min();
+        let from_portal = self.portal_coords.iter().map(|&portal| distance(portal, target_coord)).min();
+        match (to_portal, from_portal) {
+            (Some(to_portal), Some(from_portal)) => direct.min(to_portal + from_portal),
+            _ => direct,
+        }
     }
 
     fn node_move_cost(&self, parent_move_cost: i32) -> i32 {
         parent_move_cost + 1
     }
 
+    // Keys are picked up on the way, a door is only crossed with the key held.
     pub fn compute_path(
         &mut self,
         board: &Board,
@@ -65,70 +112,117 @@ impl AStarAlgo {
         target_coord: UCoord2,
         walkable_content: &[Content],
     ) -> Vec<UCoord2> {
-        self.clear();
-
-        self.closed_list
-            .insert(start_coord, Node::new(start_coord, 0, 0, start_coord));
+        self.compute_path_in_time(board, start_coord, target_coord, walkable_content, 0, |_, _| true)
+    }
 
-        let mut parent_coord = start_coord;
+    // Space-time search : is_safe tells whether a square can be reached at a turn, counted from 1 for the first move.
+    // The turns are told apart up to horizon, beyond they are all the same state and is_safe is not asked.
+    pub fn compute_path_in_time(
+        &mut self,
+        board: &Board,
+        start_coord: UCoord2,
+        target_coord: UCoord2,
+        walkable_content: &[Content],
+        horizon: usize,
+        is_safe: impl Fn(UCoord2, usize) -> bool,
+    ) -> Vec<UCoord2> {
+        self.clear(board.width(), board.height());
+        self.portal_coords = board
+            .portal_coords()
+            .iter()
+            .copied()
+            .filter(|&portal| board.portal_exit(portal).is_some())
+            .collect();
+        self.topology = Arc::clone(board.topology());
+
+        let start_state = (start_coord, board.keys(), 0);
+        self.push_node(start_coord, Node {
+            keys: start_state.1,
+            turn: 0,
+            move_cost: 0,
+            parent_state: start_state,
+            closed: true,
+        });
+
+        let mut parent_state = start_state;
         let mut parent_move_cost = 0;
 
-        while parent_coord != target_coord {
-            let mut available_neighbours: Vec<Node> = board
+        while parent_state.0 != target_coord {
+            let (parent_coord, parent_keys, parent_turn) = parent_state;
+            let turn = parent_turn + 1;
+            let move_cost = self.node_move_cost(parent_move_cost);
+            for coord in board
                 // .walkable_neighbours_iter(parent_coord, Direction::Left)
                 .neighbours_in_board_iter(parent_coord, Direction::Left)
-                .filter(|coord| {
-                    walkable_content.contains(&board.get_content(coord))
-                        && !self.closed_list.contains_key(coord)
-                })
-                .map(|coord| {
-                    Node::new(
+            {
+                let content = board.get_content(&coord);
+                if !content.is_walkable(walkable_content, parent_keys) || (turn <= horizon && !is_safe(coord, turn)) {
+                    continue;
+                }
+                let keys = match content {
+                    Content::Key(color) => parent_keys.with(color),
+                    _ => parent_keys,
+                };
+                let state = (coord, keys, turn.min(horizon));
+
+                match self.node_mut(state) {
+                    Some(node) if node.closed || node.move_cost <= move_cost => continue,
+                    Some(node) => {
+                        node.move_cost = move_cost;
+                        node.parent_state = parent_state;
+                    }
+                    None => self.push_node(
                         coord,
-                        self.node_move_cost(parent_move_cost),
-                        self.heuristic(coord, target_coord),
-                        parent_coord,
-                    )
-                })
-                .collect();
-
-            for new_node in available_neighbours.drain(..) {
-                self.open_list
-                    .entry(new_node.coord)
-                    .and_modify(|existing_node| {
-                        if existing_node.move_cost > new_node.move_cost {
-                            existing_node.move_cost = new_node.move_cost;
-                            existing_node.parent_coord = new_node.parent_coord;
-                        }
-                    })
-                    .or_insert(new_node);
+                        Node {
+                            keys,
+                            turn: state.2,
+                            move_cost,
+                            parent_state,
+                            closed: false,
+                        },
+                    ),
+                }
+                let heuristic = self.heuristic(coord, target_coord);
+                self.open_list.push(Reverse((
+                    move_cost + heuristic,
+                    heuristic,
+                    coord.y,
+                    coord.x,
+                    keys,
+                    state.2,
+                    move_cost,
+                )));
             }
 
-            let (&cheapest_node_coord, _) = self
-                .open_list
-                .iter()
-                .min_by_key(|&(_, node)| node.cost())
-                .unwrap();
-
-            let cheapest_node = self.open_list.remove(&cheapest_node_coord).unwrap();
-
-            parent_coord = cheapest_node.coord;
-            parent_move_cost = cheapest_node.move_cost;
-
-            self.closed_list.insert(cheapest_node.coord, cheapest_node);
+            // The target cannot be reached, the path is empty.
+            parent_state = loop {
+                let Reverse((_, _, y, x, keys, turn, move_cost)) = match self.open_list.pop() {
+                    Some(entry) => entry,
+                    None => return Vec::new(),
+                };
+                let state = (UCoord2::new(x, y), keys, turn);
+                let node = self.node_mut(state).unwrap();
+                if !node.closed && node.move_cost == move_cost {
+                    node.closed = true;
+                    parent_move_cost = move_cost;
+                    break state;
+                }
+            };
         }
 
-        self.generate_path(target_coord)
+        self.generate_path(parent_state)
     }
 
-    fn generate_path(&self, target_coord: UCoord2) -> Vec<UCoord2> {
-        let mut path: Vec<_> = iter::successors(self.closed_list.get(&target_coord), |&node| {
-            if node.parent_coord == node.coord {
+    fn generate_path(&self, target_state: State) -> Vec<UCoord2> {
+        let mut path: Vec<_> = iter::successors(Some(target_state), |&state| {
+            let parent_state = self.node(state).unwrap().parent_state;
+            if parent_state == state {
                 None
             } else {
-                self.closed_list.get(&node.parent_coord)
+                Some(parent_state)
             }
         })
-        .map(|node| node.coord)
+        .map(|state| state.0)
         .collect();
         path.remove(path.len() - 1);
         path.reverse();
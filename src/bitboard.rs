@@ -0,0 +1,347 @@
+use std::ops::Range;
+
+use crate::board::Content;
+use crate::range_helper::centered_range;
+use crate::vect2_t::{UCoord2, UDelta2};
+
+// The four CodinGame moves, in the order of Movement::orthogonal.
+const ORTHOGONAL: [(i32, i32); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+
+// One bit per square, each row stored in whole 64 bit words so that a word holds 64 squares of a row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitboard {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+}
+
+impl Bitboard {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            words: vec![0; words_per_row * height],
+            width,
+            height,
+            words_per_row,
+        }
+    }
+
+    pub fn full(width: usize, height: usize) -> Self {
+        let mut bitboard = Self::new(width, height);
+        for word in bitboard.words.iter_mut() {
+            *word = !0;
+        }
+        bitboard.clear_padding();
+        bitboard
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, coord: UCoord2) -> bool {
+        coord.x < self.width
+            && coord.y < self.height
+            && self.words[self.word_index(coord)] & (1 << (coord.x % 64)) != 0
+    }
+
+    pub fn set(&mut self, coord: UCoord2, value: bool) {
+        let idx = self.word_index(coord);
+        if value {
+            self.words[idx] |= 1 << (coord.x % 64);
+        } else {
+            self.words[idx] &= !(1 << (coord.x % 64));
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    // Squares of the set bits, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = UCoord2> + '_ {
+        self.words.iter().enumerate().flat_map(move |(idx, &word)| {
+            let y = idx / self.words_per_row;
+            let x0 = (idx % self.words_per_row) * 64;
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let x = x0 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(UCoord2::new(x, y))
+            })
+        })
+    }
+
+    pub fn and(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & b)
+    }
+
+    pub fn or(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a | b)
+    }
+
+    pub fn and_not(&self, other: &Self) -> Self {
+        self.zip_words(other, |a, b| a & !b)
+    }
+
+    // Every bit moved by the delta, the bits moved off the board are lost.
+    pub fn shifted(&self, delta: UDelta2) -> Self {
+        let mut shifted = Self::new(self.width, self.height);
+        for y in 0..self.height {
+            let source_y = y as i64 - delta.y as i64;
+            if source_y < 0 || source_y >= self.height as i64 {
+                continue;
+            }
+            let source = self.row(source_y as usize);
+            let row = shifted.row_mut(y);
+            shift_row(source, row, delta.x as i64);
+        }
+        shifted.clear_padding();
+        shifted
+    }
+
+    // The bits and the squares one of the deltas away from them.
+    fn dilated(&self, deltas: &[UDelta2]) -> Self {
+        deltas
+            .iter()
+            .fold(self.clone(), |dilated, &delta| dilated.or(&self.shifted(delta)))
+    }
+
+    // The bits and their four orthogonal neighbours.
+    pub fn dilated_orthogonal(&self) -> Self {
+        let deltas: Vec<UDelta2> = ORTHOGONAL.iter().map(UDelta2::from).collect();
+        self.dilated(&deltas)
+    }
+
+    // Squares of passable reached from start with the four orthogonal moves, start included. Each pass fills whole
+    // runs of a row at once, so the number of passes follows the turns of the paths, not their length.
+    pub fn flood_fill(start: UCoord2, passable: &Self) -> Self {
+        let mut filled = Self::new(passable.width, passable.height);
+        filled.set(start, true);
+        let passable = passable.or(&filled);
+
+        loop {
+            let mut next = filled.or(&filled.shifted(UDelta2::new(0, 1)).and(&passable));
+            next = next.or(&filled.shifted(UDelta2::new(0, -1)).and(&passable));
+            for y in 0..next.height {
+                fill_row(next.row_mut(y), passable.row(y));
+            }
+            if next == filled {
+                return filled;
+            }
+            filled = next;
+        }
+    }
+
+    // Whether a bit is set in the rectangle of the ranges, a word at a time.
+    pub fn any_in(&self, x_range: Range<usize>, y_range: Range<usize>) -> bool {
+        let x_range = x_range.start..x_range.end.min(self.width);
+        if x_range.is_empty() {
+            return false;
+        }
+        let first_word = x_range.start / 64;
+        let last_word = (x_range.end - 1) / 64;
+        y_range.take_while(|&y| y < self.height).any(|y| {
+            let row = self.row(y);
+            (first_word..=last_word).any(|idx| {
+                let from = if idx == first_word { x_range.start % 64 } else { 0 };
+                let to = if idx == last_word { (x_range.end - 1) % 64 } else { 63 };
+                let mask = (!0u64 >> (63 - to)) & (!0u64 << from);
+                row[idx] & mask != 0
+            })
+        })
+    }
+
+    fn word_index(&self, coord: UCoord2) -> usize {
+        coord.y * self.words_per_row + coord.x / 64
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u64] {
+        &mut self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    fn zip_words(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "bitboards of different sizes"
+        );
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..*self
+        }
+    }
+
+    // The bits past the width stay clear.
+    fn clear_padding(&mut self) {
+        if self.width.is_multiple_of(64) {
+            return;
+        }
+        let mask = !0u64 >> (64 - self.width % 64);
+        let last_word = self.words_per_row - 1;
+        for y in 0..self.height {
+            self.row_mut(y)[last_word] &= mask;
+        }
+    }
+}
+
+// Bits of the source row moved by dx squares, towards the higher x when positive.
+fn shift_row(source: &[u64], row: &mut [u64], dx: i64) {
+    let word_count = source.len() as i64;
+    let (word_shift, bit_shift) = (dx.div_euclid(64), dx.rem_euclid(64) as u32);
+    for (idx, word) in row.iter_mut().enumerate() {
+        let source_word = |idx: i64| {
+            if (0..word_count).contains(&idx) {
+                source[idx as usize]
+            } else {
+                0
+            }
+        };
+        let low = source_word(idx as i64 - word_shift);
+        let below = source_word(idx as i64 - word_shift - 1);
+        *word = if bit_shift == 0 {
+            low
+        } else {
+            (low << bit_shift) | (below >> (64 - bit_shift))
+        };
+    }
+}
+
+// Spread the set bits of the row over the runs of passable bits holding them, across the words of the row.
+fn fill_row(row: &mut [u64], passable: &[u64]) {
+    let mut carry = false;
+    for (word, &passable) in row.iter_mut().zip(passable) {
+        let generator = *word | (carry as u64 & passable);
+        *word = fill_towards_high_bits(generator, passable);
+        carry = *word >> 63 != 0;
+    }
+    let mut carry = false;
+    for (word, &passable) in row.iter_mut().zip(passable).rev() {
+        let generator = *word | ((carry as u64) << 63 & passable);
+        *word = fill_towards_low_bits(generator, passable);
+        carry = *word & 1 != 0;
+    }
+}
+
+// Kogge-Stone occluded fills : the generator bits spread over the consecutive propagator bits.
+fn fill_towards_high_bits(mut generator: u64, mut propagator: u64) -> u64 {
+    for shift in [1, 2, 4, 8, 16, 32] {
+        generator |= propagator & (generator << shift);
+        propagator &= propagator << shift;
+    }
+    generator
+}
+
+fn fill_towards_low_bits(mut generator: u64, mut propagator: u64) -> u64 {
+    for shift in [1, 2, 4, 8, 16, 32] {
+        generator |= propagator & (generator >> shift);
+        propagator &= propagator >> shift;
+    }
+    generator
+}
+
+// What the board knows, one bitboard per kind of square : the walls, the squares walked like empty ones (empty
+// squares and keys), the unknown squares and the doors. Portals and hazards are in none of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardBits {
+    walls: Bitboard,
+    free: Bitboard,
+    unknown: Bitboard,
+    doors: Bitboard,
+}
+
+impl BoardBits {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            walls: Bitboard::new(width, height),
+            free: Bitboard::new(width, height),
+            unknown: Bitboard::full(width, height),
+            doors: Bitboard::new(width, height),
+        }
+    }
+
+    pub fn walls(&self) -> &Bitboard {
+        &self.walls
+    }
+
+    pub fn free(&self) -> &Bitboard {
+        &self.free
+    }
+
+    pub fn unknown(&self) -> &Bitboard {
+        &self.unknown
+    }
+
+    pub fn doors(&self) -> &Bitboard {
+        &self.doors
+    }
+
+    pub fn set(&mut self, coord: UCoord2, content: Content) {
+        self.walls.set(coord, content == Content::Wall);
+        self.free
+            .set(coord, matches!(content, Content::Empty | Content::Key(_)));
+        self.unknown.set(coord, content == Content::Unknown);
+        self.doors.set(coord, matches!(content, Content::Door(_)));
+    }
+
+    // Free squares next to an unknown one.
+    pub fn frontier(&self) -> Bitboard {
+        self.unknown.dilated_orthogonal().and(&self.free)
+    }
+
+    // Free squares reached from start.
+    pub fn reachable(&self, start: UCoord2) -> Bitboard {
+        Bitboard::flood_fill(start, &self.free)
+    }
+
+    // Whether the radar would show an unknown square from this square.
+    pub fn any_unknown_in_window(&self, center: UCoord2) -> bool {
+        self.unknown.any_in(
+            centered_range(center.x, self.unknown.width),
+            centered_range(center.y, self.unknown.height),
+        )
+    }
+
+    // Free squares whose radar window holds an unknown square.
+    pub fn revealing_squares(&self) -> Bitboard {
+        let mut window = self.unknown.clone();
+        for delta in &[(-1, 0), (1, 0)] {
+            let step = UDelta2::from(delta);
+            window = window.or(&window.shifted(step));
+            window = window.or(&window.shifted(step));
+        }
+        for delta in &[(0, -1), (0, 1)] {
+            let step = UDelta2::from(delta);
+            window = window.or(&window.shifted(step));
+            window = window.or(&window.shifted(step));
+        }
+        window.and(&self.free)
+    }
+}
+
+// Whether moves of the topology are the four orthogonal ones on every row, the only ones the bitboards follow.
+pub fn follows_orthogonal_moves(deltas_of_even_rows: &[UDelta2], deltas_of_odd_rows: &[UDelta2]) -> bool {
+    let orthogonal: Vec<UDelta2> = ORTHOGONAL.iter().map(UDelta2::from).collect();
+    deltas_of_even_rows == orthogonal.as_slice() && deltas_of_odd_rows == orthogonal.as_slice()
+}
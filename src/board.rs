@@ -1,36 +1,79 @@
 use std::{
     fmt,
-    ops::IndexMut,
+    sync::Arc,
 };
 
-use crate::vect2_t::UCoord2;
+use crate::vect2_t::{Bounds, UCoord2, UDelta2};
+use crate::bitboard::{self, BoardBits};
 use crate::direction::Direction;
-use crate::range_helper::centered_range;
+use crate::grid::Grid;
+use crate::keys::{KeyColor, KeySet};
+use crate::guards::Guard;
+use crate::movement::Movement;
+use crate::topology::Topology;
+use crate::range_helper::radar_range;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Content {
     Unknown,
     Wall,
     Empty,
+    // Picked up by walking on it, the square is empty otherwise.
+    Key(KeyColor),
+    // Passable only with the key of its colour.
+    Door(KeyColor),
+    // Stepping in moves Kirk to the other portal with the same digit.
+    Portal(u8),
+    // Lava or trap : stepping on it ends the game.
+    Hazard,
 }
+
+impl Content {
+    // Keys and open doors are walked like empty squares.
+    pub fn is_walkable(self, walkable_content: &[Content], keys: KeySet) -> bool {
+        match self {
+            Content::Key(_) => walkable_content.contains(&Content::Empty),
+            Content::Door(color) => keys.contains(color) && walkable_content.contains(&Content::Empty),
+            Content::Portal(_) => walkable_content.contains(&Content::Empty),
+            content => walkable_content.contains(&content),
+        }
+    }
+}
+
 pub struct Board {
-    view: Vec<Vec<Content>>,
+    view: Grid<Content>,
     width: usize,
     height: usize,
     rounds: usize,
     rick_current_coord: Option<UCoord2>,
     rick_start_coord: Option<UCoord2>,
-    cmd_room_coord: Option<UCoord2>,
+    // every control room seen so far, in discovery order
+    cmd_room_coords: Vec<UCoord2>,
+    // keys picked up by Kirk
+    keys: KeySet,
+    // every portal seen so far, in discovery order
+    portal_coords: Vec<UCoord2>,
+    // number of updates, and for each square the last update where the radar saw it
+    turn: usize,
+    last_seen: Grid<Option<usize>>,
+    // squares seen with a different content at the last update, in a changing labyrinth
+    changed_coords: Vec<UCoord2>,
+    // guards seen by the radar at the last update, the squares under them are empty
+    guards: Vec<Guard>,
+    // grid and moves of Kirk, the square grid with the four orthogonal moves in the CodinGame game
+    topology: Arc<dyn Topology>,
+    // the same knowledge as bitsets, when turned on
+    bits: Option<BoardBits>,
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut str = String::with_capacity((self.width * 3 + 1) * self.height);
 
-        for (y, row) in self.view.iter().enumerate() {
+        for (y, row) in self.view.rows().enumerate() {
             for (x, v) in row.iter().enumerate() {
-                let rick_is_here = self.rick_current_coord.map_or(false, |coord| coord.is(x, y));
-                let cmd_room_is_here = self.cmd_room_coord.map_or(false, |coord| coord.is(x, y));
+                let rick_is_here = self.rick_current_coord.is_some_and(|coord| coord.is(x, y));
+                let cmd_room_is_here = self.cmd_room_coords.iter().any(|coord| coord.is(x, y));
 
                 if rick_is_here {
                     str.push('K')
@@ -41,6 +84,10 @@ impl fmt::Display for Board {
                         Content::Unknown => str.push('?'),
                         Content::Empty => str.push('.'),
                         Content::Wall => str.push('#'),
+                        Content::Key(color) => str.push(color.key_byte() as char),
+                        Content::Door(color) => str.push(color.door_byte() as char),
+                        Content::Portal(label) => str.push((b'0' + label) as char),
+                        Content::Hazard => str.push('~'),
                     }
                 }
             }
@@ -88,30 +135,184 @@ impl fmt::Display for Board {
 impl Board {
     pub fn new(width: usize, height: usize, rounds: usize) -> Self {
         Self {
-            view: vec![vec![Content::Unknown; width]; height],
+            view: Grid::new(width, height, Content::Unknown),
             width,
             height,
             rounds,
             rick_current_coord: None,
             rick_start_coord: None,
-            cmd_room_coord: None,
+            cmd_room_coords: Vec::new(),
+            keys: KeySet::new(),
+            portal_coords: Vec::new(),
+            turn: 0,
+            last_seen: Grid::new(width, height, None),
+            changed_coords: Vec::new(),
+            guards: Vec::new(),
+            topology: Arc::new(Movement::orthogonal()),
+            bits: None,
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    pub fn rick_current_coord(&self) -> Option<UCoord2> {
+        self.rick_current_coord
+    }
+
     pub fn rick_start_coord(&self) -> Option<UCoord2> {
         self.rick_start_coord
     }
 
+    // The first control room seen.
     pub fn cmd_room_coord(&self) -> Option<UCoord2> {
-        self.cmd_room_coord
+        self.cmd_room_coords.first().copied()
+    }
+
+    pub fn cmd_room_coords(&self) -> &[UCoord2] {
+        &self.cmd_room_coords
+    }
+
+    pub fn keys(&self) -> KeySet {
+        self.keys
+    }
+
+    // Known squares holding a key, with their colour.
+    pub fn key_coords(&self) -> impl Iterator<Item = (UCoord2, KeyColor)> + '_ {
+        self.view.iter().filter_map(|(coord, content)| match content {
+            Content::Key(color) => Some((coord, *color)),
+            _ => None,
+        })
+    }
+
+    // Colours of the known doors.
+    pub fn door_colors(&self) -> KeySet {
+        self.view
+            .iter()
+            .fold(KeySet::new(), |colors, (_, content)| match content {
+                Content::Door(color) => colors.with(*color),
+                _ => colors,
+            })
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn last_seen(&self, coord: &UCoord2) -> Option<usize> {
+        self.last_seen[*coord]
+    }
+
+    pub fn changed_coords(&self) -> &[UCoord2] {
+        &self.changed_coords
+    }
+
+    pub fn topology(&self) -> &Arc<dyn Topology> {
+        &self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Arc<dyn Topology>) {
+        self.topology = topology;
+    }
+
+    // Square grid with another movement model.
+    pub fn set_movement(&mut self, movement: Movement) {
+        self.set_topology(Arc::new(movement));
+    }
+
+    pub fn bits(&self) -> Option<&BoardBits> {
+        self.bits.as_ref()
+    }
+
+    // Keep the knowledge as bitboards too, or not.
+    pub fn set_bitboard(&mut self, enabled: bool) {
+        self.bits = enabled.then(|| {
+            let mut bits = BoardBits::new(self.width, self.height);
+            for (coord, &content) in self.view.iter() {
+                bits.set(coord, content);
+            }
+            bits
+        });
+    }
+
+    // The bitboards when they answer like the squares : the four orthogonal moves on a grid with edges, and no
+    // door or portal, which are not in the bitboards.
+    pub fn exact_bits(&self) -> Option<&BoardBits> {
+        let follows_topology = !self.topology.wraps()
+            && bitboard::follows_orthogonal_moves(
+                self.topology.deltas(UCoord2::new(0, 0)),
+                self.topology.deltas(UCoord2::new(0, 1)),
+            );
+        self.bits
+            .as_ref()
+            .filter(|bits| follows_topology && self.portal_coords.is_empty() && bits.doors().is_empty())
+    }
+
+    pub fn guards(&self) -> &[Guard] {
+        &self.guards
+    }
+
+    pub fn portal_coords(&self) -> &[UCoord2] {
+        &self.portal_coords
+    }
+
+    // The other known portal with the same digit.
+    pub fn portal_exit(&self, coord: UCoord2) -> Option<UCoord2> {
+        match self.get_content(&coord) {
+            Content::Portal(label) => self
+                .portal_coords
+                .iter()
+                .copied()
+                .find(|&exit| exit != coord && self.get_content(&exit) == Content::Portal(label)),
+            _ => None,
+        }
+    }
+
+    // Square where Kirk is after stepping on coord : the exit of a portal, or coord itself.
+    // A portal with an unknown exit is assumed to keep Kirk on it.
+    pub fn landing(&self, coord: UCoord2) -> UCoord2 {
+        self.portal_exit(coord).unwrap_or(coord)
+    }
+
+    // Direction of the move from a square to a neighbour or to the exit of a neighbouring portal.
+    pub fn direction_to(&self, from: UCoord2, to: UCoord2) -> Direction {
+        self.step_to(from, to).into()
+    }
+
+    // Move of the topology from a square to a neighbour or to the exit of a neighbouring portal.
+    pub fn step_to(&self, from: UCoord2, to: UCoord2) -> UDelta2 {
+        self.topology
+            .deltas(from)
+            .iter()
+            .copied()
+            .find(|&delta| self.neighbour(from, delta).is_some_and(|neighbour| self.landing(neighbour) == to))
+            .unwrap_or(to - from)
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.width, self.height)
     }
 
     fn coord_is_in_board(&self, coord: &UCoord2) -> bool {
-        coord.x < self.width && coord.y < self.height
+        self.bounds().contains(*coord)
+    }
+
+    // Square reached by a move, around the edges of a torus, None off the board.
+    pub fn neighbour(&self, coord: UCoord2, delta: UDelta2) -> Option<UCoord2> {
+        self.topology.neighbour(coord, delta, self.width, self.height)
     }
 
     pub fn get_content(&self, coord: &UCoord2) -> Content {
-        self.view[coord.y][coord.x]
+        self.view[*coord]
     }
 
     // pub fn get_mut(&mut self, idx: &UCoord2) -> &mut Content {
@@ -158,45 +359,161 @@ impl Board {
     //     }
     // }
 
-    pub fn update_with(&mut self, rick_coord: UCoord2, data: &Vec<String>) {
+    pub fn update_with(&mut self, rick_coord: UCoord2, data: &[String]) {
         if self.rick_start_coord.is_none() {
             self.rick_start_coord = Some(rick_coord);
         }
 
-        self.rick_current_coord = Some(rick_coord);
+        let previous_coord = self.rick_current_coord.replace(rick_coord);
+        if let Some(previous_coord) = previous_coord {
+            self.discover_portal_exit(previous_coord, rick_coord);
+        }
 
-        let viewport_y_range = centered_range(rick_coord.y, self.height);
-        let viewport_x_range = centered_range(rick_coord.x, self.width);
+        self.begin_update();
+        self.merge_view(rick_coord, data);
 
-        for y in viewport_y_range {
-            let row = data[y].trim().as_bytes();
+        if let Some(Content::Key(color)) = self.view.get(rick_coord) {
+            self.keys.insert(*color);
+        }
+    }
 
-            for x in viewport_x_range.clone() {
-                let content = self.view[y].index_mut(x);
+    // A new round of radar views : the changes and the guards seen are the ones of this round only.
+    pub fn begin_update(&mut self) {
+        self.turn += 1;
+        self.changed_coords.clear();
+        self.guards.clear();
+    }
+
+    // Squares of a radar view centred on coord, Kirk's or another explorer's one.
+    pub fn merge_view(&mut self, coord: UCoord2, data: &[String]) {
+        let viewport_y_range = radar_range(coord.y, self.height, self.topology.wraps());
+        let viewport_x_range = radar_range(coord.x, self.width, self.topology.wraps());
 
-                if *content == Content::Unknown {
-                    match row[x] {
-                        b'C' => {
-                            *content = Content::Empty;
-                            self.cmd_room_coord = Some((x, y).into())
+        // Missing rows or squares of a truncated view stay unknown.
+        for y in viewport_y_range {
+            let row = data.get(y).map_or(&[][..], |row| row.trim().as_bytes());
+
+            for &x in &viewport_x_range {
+                if let Some(&byte) = row.get(x) {
+                    let byte = match Guard::from_byte((x, y).into(), byte) {
+                        Some(guard) => {
+                            self.guards.push(guard);
+                            b'.'
                         }
-                        b'#' => *content = Content::Wall,
-                        b'.' | b'T' => *content = Content::Empty,
-                        _ => (),
+                        None => byte,
                     };
+                    self.discover((x, y).into(), byte);
+                    self.last_seen[y][x] = Some(self.turn);
                 }
             }
         }
     }
 
+    // Board with the whole labyrinth already known, as if the radar had seen everything.
+    pub fn from_rows(data: &[String], rounds: usize) -> Self {
+        let height = data.len();
+        let width = data.first().map_or(0, |row| row.trim().len());
+        let mut board = Self::new(width, height, rounds);
+
+        for (y, row) in data.iter().enumerate() {
+            for (x, &byte) in row.trim().as_bytes().iter().enumerate() {
+                if byte == b'T' {
+                    board.rick_start_coord = Some((x, y).into());
+                }
+                let byte = if Guard::from_byte((x, y).into(), byte).is_some() { b'.' } else { byte };
+                board.discover((x, y).into(), byte);
+            }
+        }
+
+        board
+    }
+
+    fn discover(&mut self, coord: UCoord2, byte: u8) {
+        let content = &mut self.view[coord];
+
+        // A wall opened or closed since the square was last seen.
+        match (*content, byte) {
+            (Content::Empty, b'#') => {
+                *content = Content::Wall;
+                self.changed_coords.push(coord);
+            }
+            (Content::Wall, b'.') | (Content::Wall, b'T') => {
+                *content = Content::Empty;
+                self.changed_coords.push(coord);
+            }
+            _ => (),
+        }
+
+        if *content == Content::Unknown {
+            match byte {
+                b'C' => {
+                    *content = Content::Empty;
+                    self.cmd_room_coords.push(coord)
+                }
+                b'#' => *content = Content::Wall,
+                b'.' | b'T' => *content = Content::Empty,
+                b'~' => *content = Content::Hazard,
+                b'0'..=b'9' => {
+                    *content = Content::Portal(byte - b'0');
+                    self.portal_coords.push(coord)
+                }
+                byte => {
+                    if let Some(color) = KeyColor::from_key_byte(byte) {
+                        *content = Content::Key(color);
+                    } else if let Some(color) = KeyColor::from_door_byte(byte) {
+                        *content = Content::Door(color);
+                    }
+                }
+            };
+        }
+
+        let content = *content;
+        if let Some(bits) = &mut self.bits {
+            bits.set(coord, content);
+        }
+    }
+
+    // The view shows Kirk on his square, not its content. When he did not move to a neighbour, he went through
+    // a portal next to his previous square and stands on its exit.
+    fn discover_portal_exit(&mut self, previous_coord: UCoord2, rick_coord: UCoord2) {
+        if !self.coord_is_in_board(&rick_coord)
+            || self.get_content(&rick_coord) != Content::Unknown
+            || self.topology.deltas(previous_coord).iter().any(|&delta| self.neighbour(previous_coord, delta) == Some(rick_coord))
+        {
+            return;
+        }
+
+        let label = self
+            .neighbours_in_board_iter(previous_coord, Direction::Left)
+            .filter(|coord| self.portal_exit(*coord).is_none())
+            .find_map(|coord| match self.get_content(&coord) {
+                Content::Portal(label) => Some(label),
+                _ => None,
+            });
+        if let Some(label) = label {
+            self.discover(rick_coord, b'0' + label);
+        }
+    }
+
+    // Squares reached in one move of the topology, starting with first_dir when it is one of the moves.
+    // A neighbouring portal leads to its exit.
     pub fn neighbours_in_board_iter(
         &self,
         start: UCoord2,
         first_dir: Direction,
     ) -> impl Iterator<Item = UCoord2> + '_ {
-        start
-            .neighbours_iter(first_dir)
-            .filter(move |coord| self.coord_is_in_board(coord))
+        let is_blocked = move |coord: UCoord2| !self.coord_is_in_board(&coord) || self.get_content(&coord) == Content::Wall;
+        let deltas = self.topology.deltas(start);
+        let first = deltas.iter().position(|&delta| delta == first_dir.to_delta()).unwrap_or(0);
+        deltas
+            .iter()
+            .cycle()
+            .skip(first)
+            .take(deltas.len())
+            .copied()
+            .filter(move |&delta| self.topology.allows(start, delta, &is_blocked))
+            .filter_map(move |delta| self.neighbour(start, delta))
+            .map(move |coord| self.landing(coord))
     }
 
 }
@@ -0,0 +1,138 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+// Modules of the bot, in dependency order. The simulator, referee and tooling modules are not shipped.
+pub const SOLVER_MODULES: &[&str] = &[
+    "range_helper",
+    "vect2_t",
+    "grid",
+    "bitboard",
+    "direction",
+    "movement",
+    "topology",
+    "keys",
+    "guards",
+    "a_star_algo",
+    "board",
+    "exploration",
+    "tour",
+    "solver",
+    "protocol",
+];
+
+const HEADER: &str = "// Generated by `the_labyrinth bundle`, do not edit: the sources are in src/.
+#![allow(dead_code)]
+";
+
+// The bot reads the real stdin, as the CodinGame judge runs it.
+const MAIN: &str = "
+fn main() {
+    if let Err(err) = protocol::play(std::io::stdin().lock(), std::io::stdout()) {
+        eprintln!(\"Protocol error: {}\", err);
+        std::process::exit(1);
+    }
+}
+";
+
+// Inline the solver modules of src_dir as `mod` blocks, with a main reading stdin, in a single source file.
+pub fn bundle(src_dir: &Path) -> io::Result<String> {
+    let mut bundle = String::from(HEADER);
+
+    for module in SOLVER_MODULES {
+        let path = src_dir.join(format!("{}.rs", module));
+        let source = fs::read_to_string(&path)?;
+        bundle.push_str(&format!("\nmod {} {{\n", module));
+        bundle.push_str(&inline_module(
+            &strip_tests(&source),
+            &src_dir.join(module),
+        )?);
+        bundle.push_str("}\n");
+    }
+
+    bundle.push_str(MAIN);
+    Ok(bundle)
+}
+
+// Replace the `mod name;` declarations of a module by the content of their file, found in module_dir.
+fn inline_module(source: &str, module_dir: &Path) -> io::Result<String> {
+    let mut inlined = String::new();
+
+    for line in source.lines() {
+        let declaration = line.trim();
+        let name = declaration
+            .strip_prefix("pub mod ")
+            .or_else(|| declaration.strip_prefix("mod "))
+            .and_then(|rest| rest.strip_suffix(';'));
+
+        match name {
+            Some(name) => {
+                let path = module_dir.join(format!("{}.rs", name));
+                let source = fs::read_to_string(&path)?;
+                inlined.push_str(&format!("{} {{\n", declaration.trim_end_matches(';')));
+                inlined.push_str(&inline_module(
+                    &strip_tests(&source),
+                    &module_dir.join(name),
+                )?);
+                inlined.push_str("}\n");
+            }
+            None => {
+                inlined.push_str(line);
+                inlined.push('\n');
+            }
+        }
+    }
+
+    Ok(inlined)
+}
+
+// Remove the `#[test]` and `#[cfg(test)]` items, they only make the submission longer.
+fn strip_tests(source: &str) -> String {
+    let mut stripped = String::new();
+    let mut lines = source.lines();
+
+    while let Some(line) = lines.next() {
+        if line.trim() != "#[test]" && line.trim() != "#[cfg(test)]" {
+            stripped.push_str(line);
+            stripped.push('\n');
+            continue;
+        }
+
+        // skip the item, a single line one or up to the closing brace of its indentation level
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let closing = format!("{}}}", indent);
+        if let Some(item_line) = lines.next() {
+            if !item_line.trim_end().ends_with(';') {
+                lines
+                    .by_ref()
+                    .find(|item_line| item_line.trim_end() == closing);
+            }
+        }
+    }
+
+    stripped
+}
+
+// Compile the bundle with rustc alone, no crate is available on the judge. Returns the path of the executable.
+pub fn verify(bundle_path: &Path) -> io::Result<PathBuf> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let executable = bundle_path.with_extension(env::consts::EXE_EXTENSION);
+
+    let output = Command::new(rustc)
+        .args(["--edition", "2018", "--crate-type", "bin", "-O", "-o"])
+        .arg(&executable)
+        .arg(bundle_path)
+        .output()?;
+
+    if output.status.success() {
+        Ok(executable)
+    } else {
+        Err(io::Error::other(format!(
+            "{} does not compile:\n{}",
+            bundle_path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        )))
+    }
+}
@@ -1,4 +1,4 @@
-use crate::vect2_t::{UCoord2, UDelta2};
+use crate::vect2_t::{Bounds, UCoord2, UDelta2};
 
 use std::{
     fmt, iter
@@ -19,16 +19,7 @@ impl fmt::Display for Direction {
 }
 
 impl Direction {
-    fn id(&self) -> usize {
-        match self {
-            Self::Right => 0,
-            Self::Down => 1,
-            Self::Left => 2,
-            Self::Up => 3,
-        }
-    }
-
-    fn array() -> [Self; 4] {
+    pub fn array() -> [Self; 4] {
         [Self::Right, Self::Down, Self::Left, Self::Up]
     }
 
@@ -49,7 +40,7 @@ impl Direction {
             Self::Up => Self::Right,
         }
     }
-    fn reverse(&self) -> Self {
+    pub fn reverse(&self) -> Self {
         match self {
             Self::Right => Self::Left,
             Self::Down => Self::Up,
@@ -57,7 +48,17 @@ impl Direction {
             Self::Up => Self::Down,
         }
     }
-    fn to_delta(&self) -> UDelta2 {
+    pub fn from_label(label: &str) -> Option<Direction> {
+        match label {
+            "RIGHT" => Some(Self::Right),
+            "DOWN" => Some(Self::Down),
+            "LEFT" => Some(Self::Left),
+            "UP" => Some(Self::Up),
+            _ => None,
+        }
+    }
+
+    pub fn to_delta(self) -> UDelta2 {
         match self {
             Self::Right => (1, 0).into(),
             Self::Down => (0, 1).into(),
@@ -65,24 +66,7 @@ impl Direction {
             Self::Up => (0, -1).into(),
         }
     }
-    fn from_delta(vect: &UDelta2) -> Direction {
-        match (vect.x, vect.y) {
-            (1, 0) => Self::Right,
-            (0, 1) => Self::Down,
-            (-1, 0) => Self::Left,
-            (0, -1) => Self::Up,
-            _ => panic!(),
-        }
-    }
-    fn apply_to_coord(&self, start: UCoord2) -> UCoord2 {
-        start + self.to_delta()
-    }
-
-    fn iter_from(start: Direction) -> impl Iterator<Item = Direction> {
-        iter::successors(Some(start), |dir| Some(dir.next()))
-    }
-
-    fn successors(self) -> impl Iterator<Item = Direction> {
+    pub fn successors(self) -> impl Iterator<Item = Direction> {
         iter::successors(Some(self), |&dir| Some(dir.next()))
     }
 }
@@ -148,4 +132,16 @@ impl UCoord2 {
             .take(4)
             .map(move |dir| self.get_neighbour(&dir))
     }
+
+    // Same, without the neighbours off the grid, for maps without walls on their border.
+    pub fn neighbours_in(
+        self,
+        first_dir: Direction,
+        bounds: Bounds,
+    ) -> impl Iterator<Item = UCoord2> {
+        first_dir
+            .successors()
+            .take(4)
+            .filter_map(move |dir| self.neighbour_in(dir.to_delta(), bounds))
+    }
 }
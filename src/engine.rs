@@ -1,179 +1,453 @@
-mod range_helper;
-mod vect2_t;
-mod direction;
-mod a_star_algo;
-mod board;
-mod exploration;
-mod game_sample;
-
-use a_star_algo::AStarAlgo;
-use board::{Board, Content};
-use game_sample::GameSample;
-use direction::Direction;
-use vect2_t::UCoord2;
+// Without features the tool is the CodinGame bot only, reading stdin or a game server. The other commands come with
+// the features they need, `--features cli` builds them all.
 
+#[cfg(feature = "simulator")]
+use std::{process::Command, time::Duration};
+#[cfg(any(all(feature = "simulator", any(feature = "generator", feature = "render")), feature = "cli"))]
+use std::fs;
+#[cfg(any(feature = "simulator", feature = "cli"))]
+use std::path::Path;
+use std::{
+    env,
+    io::{self, BufReader},
+    net::TcpStream,
+    process,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+#[cfg(feature = "generator")]
+use rand::{rngs::StdRng, SeedableRng};
+
+#[cfg(feature = "cli")]
+use the_labyrinth::bundle;
+#[cfg(all(feature = "simulator", feature = "generator"))]
+use the_labyrinth::fuzz;
+#[cfg(feature = "generator")]
+use the_labyrinth::generator::Labyrinth;
+#[cfg(feature = "simulator")]
+use the_labyrinth::game_sample::{GameSample, Outcome, SampleError, FUEL};
+use the_labyrinth::protocol;
+#[cfg(feature = "simulator")]
+use the_labyrinth::referee::{self, TurnTimeouts};
+#[cfg(all(feature = "simulator", feature = "render"))]
+use the_labyrinth::render;
+#[cfg(feature = "simulator")]
+use the_labyrinth::server;
+#[cfg(feature = "simulator")]
+use the_labyrinth::solver::Solver;
+#[cfg(all(feature = "simulator", feature = "render"))]
+use the_labyrinth::stats::GameStats;
+#[cfg(feature = "simulator")]
+use the_labyrinth::team_game::TeamGame;
+
+#[cfg(feature = "simulator")]
 const LABYRINTH_SAMPLE_FILENAME: &str = "samples/lab8.txt";
+#[cfg(all(feature = "simulator", feature = "generator"))]
+const FUZZ_FINDINGS_DIR: &str = "fuzz_findings";
+#[cfg(feature = "cli")]
+const BUNDLE_FILENAME: &str = "target/submission/main.rs";
 
+const USAGE: &str = "usage:
+    the_labyrinth
+    the_labyrinth export <labyrinth file> <map-svg|board-svg|trace-svg|animated-svg|png-frames|html|heatmap-svg|hex-svg> <output>
+    the_labyrinth stats <labyrinth file>...
+    the_labyrinth team <labyrinth file>
+    the_labyrinth generate <width> <height> <seed> [loop ratio]
+    the_labyrinth stdin
+    the_labyrinth bundle [output]
+    the_labyrinth serve <labyrinth file> <tcp|unix> <address|socket path>
+    the_labyrinth connect <tcp|unix> <address|socket path>
+    the_labyrinth referee <labyrinth file> [--turn-timeout <ms>] <bot command> [bot arguments]...
+    the_labyrinth fuzz <labyrinth_file|protocol|update_with|all> [iterations] [seed]
+    the_labyrinth fuzz-repro <labyrinth_file|protocol|update_with> <input file>
+The commands are built with the features they need, --features cli builds them all.
+Without features, only stdin and connect are there and the_labyrinth alone plays stdin.";
+
+#[cfg(feature = "generator")]
+// A bad number is a usage error, like a bad --turn-timeout.
 macro_rules! parse_input {
     ($x:expr, $t:ident) => {
-        $x.trim().parse::<$t>().unwrap()
+        $x.trim().parse::<$t>().unwrap_or_else(|_| {
+            eprintln!("Invalid {} {:?}, expected {}", stringify!($x), $x, stringify!($t));
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        })
     };
 }
 
 fn main() {
-    let mut game_sample = GameSample::new(LABYRINTH_SAMPLE_FILENAME);
-    // game_sample.debug();
-    // return;
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let mut stdin = game_sample.init_data_reader();
-    // let stdin = io::stdin(); // Uncomment this line if you want to test your app in codingame environment (and comment the above lines in main)
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        #[cfg(feature = "simulator")]
+        [] => play_sample(LABYRINTH_SAMPLE_FILENAME),
+        #[cfg(not(feature = "simulator"))]
+        [] => play_stdin(),
+        #[cfg(all(feature = "simulator", feature = "render"))]
+        ["export", labyrinth_filename, kind, output] => export(labyrinth_filename, kind, output),
+        #[cfg(all(feature = "simulator", feature = "render"))]
+        ["stats", labyrinth_filenames @ ..] if !labyrinth_filenames.is_empty() => {
+            for labyrinth_filename in labyrinth_filenames {
+                print_stats(labyrinth_filename)
+            }
+        }
+        #[cfg(feature = "simulator")]
+        ["team", labyrinth_filename] => play_team(labyrinth_filename),
+        #[cfg(feature = "generator")]
+        ["generate", width, height, seed] => generate(width, height, seed, "0.1"),
+        #[cfg(feature = "generator")]
+        ["generate", width, height, seed, loop_ratio] => generate(width, height, seed, loop_ratio),
+        ["stdin"] => play_stdin(),
+        #[cfg(feature = "cli")]
+        ["bundle"] => write_bundle(BUNDLE_FILENAME),
+        #[cfg(feature = "cli")]
+        ["bundle", output] => write_bundle(output),
+        #[cfg(feature = "simulator")]
+        ["serve", labyrinth_filename, "tcp", address] => {
+            exit_on_error(server::serve_tcp(address, labyrinth_filename))
+        }
+        #[cfg(all(unix, feature = "simulator"))]
+        ["serve", labyrinth_filename, "unix", path] => {
+            exit_on_error(server::serve_unix(Path::new(path), labyrinth_filename))
+        }
+        #[cfg(feature = "simulator")]
+        ["referee", labyrinth_filename, "--turn-timeout", turn_timeout, command @ ..]
+            if !command.is_empty() =>
+        {
+            let timeouts = TurnTimeouts {
+                turn: Duration::from_millis(parse_turn_timeout(turn_timeout)),
+                ..TurnTimeouts::default()
+            };
+            referee_bot(labyrinth_filename, command, timeouts)
+        }
+        #[cfg(feature = "simulator")]
+        ["referee", labyrinth_filename, command @ ..] if !command.is_empty() => {
+            referee_bot(labyrinth_filename, command, TurnTimeouts::default())
+        }
+        ["connect", "tcp", address] => {
+            let stream = TcpStream::connect(address).unwrap_or_else(|err| {
+                eprintln!("Cannot connect to {}: {}", address, err);
+                process::exit(1);
+            });
+            stream.set_nodelay(true).unwrap();
+            play_stream(BufReader::new(&stream), &stream);
+        }
+        #[cfg(unix)]
+        ["connect", "unix", path] => {
+            let stream = UnixStream::connect(path).unwrap_or_else(|err| {
+                eprintln!("Cannot connect to {}: {}", path, err);
+                process::exit(1);
+            });
+            play_stream(BufReader::new(&stream), &stream);
+        }
+        #[cfg(all(feature = "simulator", feature = "generator"))]
+        ["fuzz", target] => run_fuzz(target, "10000", "0"),
+        #[cfg(all(feature = "simulator", feature = "generator"))]
+        ["fuzz", target, iterations] => run_fuzz(target, iterations, "0"),
+        #[cfg(all(feature = "simulator", feature = "generator"))]
+        ["fuzz", target, iterations, seed] => run_fuzz(target, iterations, seed),
+        #[cfg(all(feature = "simulator", feature = "generator"))]
+        ["fuzz-repro", target, input] => fuzz_repro(target, input),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
 
-    let mut input_init_line = String::new();
-    stdin.read_line(&mut input_init_line).unwrap();
-    let inputs = input_init_line.split(" ").collect::<Vec<_>>();
-    let height = parse_input!(inputs[0], usize); // number of rows.
-    let width = parse_input!(inputs[1], usize); // number of columns.
-    let rounds = parse_input!(inputs[2], usize); // number of rounds between the time the alarm countdown is activated and the time the alarm goes off.
+// Plays the labyrinth against an external bot, as the CodinGame judge would.
+#[cfg(feature = "simulator")]
+fn referee_bot(labyrinth_filename: &str, command: &[&str], timeouts: TurnTimeouts) {
+    let mut game_sample = load_sample(labyrinth_filename);
+    let mut bot = Command::new(command[0]);
+    bot.args(&command[1..]);
+
+    match referee::referee_process(&mut game_sample, &mut bot, timeouts) {
+        Ok(Outcome::Won) => println!("Won after {} moves", FUEL - game_sample.fuel_left()),
+        Ok(outcome) => {
+            println!(
+                "Game lost: {:?} after {} moves",
+                outcome,
+                FUEL - game_sample.fuel_left()
+            );
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Cannot run {}: {}", command[0], err);
+            process::exit(1);
+        }
+    }
+}
 
-    let mut board = Board::new(width, height, rounds);
+// Single file to submit on CodinGame, checked to compile without any crate.
+#[cfg(feature = "cli")]
+fn write_bundle(output: &str) {
+    let output = Path::new(output);
+    let result = bundle::bundle(Path::new("src")).and_then(|source| {
+        if let Some(dir) = output.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(output, source)?;
+        bundle::verify(output)
+    });
 
-    eprintln!("{}\n", board);
+    match result {
+        Ok(_) => println!("{} written and compiled with std only", output.display()),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
 
-    let mut exploration_engine = exploration::Engine::new();
+fn play_stdin() {
+    play_stream(io::stdin().lock(), io::stdout());
+}
 
-    let mut a_star_algo = AStarAlgo::new();
-    let mut returning_to_starting_point = false;
-    let mut return_path = Vec::new();
-    let mut return_path_iter: Option<std::slice::Iter<UCoord2>> = None;
-    let mut recalculate_aproximate_return_path_at: Option<UCoord2> = None;
+// Clean shutdown with a diagnostic on stderr when the referee sends something unexpected.
+fn play_stream<R: io::BufRead, W: io::Write>(input: R, output: W) {
+    if let Err(err) = protocol::play(input, output) {
+        eprintln!("Protocol error: {}", err);
+        process::exit(1);
+    }
+}
 
-    let mut target_coord = None;
+#[cfg(feature = "simulator")]
+fn exit_on_error(result: io::Result<()>) {
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
 
-    let mut rounds_counter = 0;
-    let mut max_rounds_left = 0;
+#[cfg(feature = "simulator")]
+fn exit_on_sample_error(labyrinth_filename: &str, err: SampleError) -> ! {
+    eprintln!("Cannot load {}: {}", labyrinth_filename, err);
+    process::exit(1);
+}
 
-    let mut input_board_buffer: Vec<_> = (0..height)
-        .map(|_| String::with_capacity(width + 1))
-        .collect();
+#[cfg(feature = "simulator")]
+fn load_sample(labyrinth_filename: &str) -> GameSample {
+    GameSample::new(labyrinth_filename)
+        .unwrap_or_else(|err| exit_on_sample_error(labyrinth_filename, err))
+}
 
-    loop {
-        let mut stdin = game_sample.round_data_reader();
-        // let stdin = io::stdin(); // Uncomment this line if you want to test your app in codingame environment (and comment the above line)
+// The solver plays the whole game, a broken line of the referee ends the program.
+#[cfg(all(feature = "simulator", feature = "render"))]
+fn play_game(labyrinth_filename: &str, game_sample: &mut GameSample) -> Solver {
+    game_sample.play().unwrap_or_else(|err| {
+        eprintln!("Cannot play {}: {}", labyrinth_filename, err);
+        process::exit(1);
+    })
+}
 
+// Milliseconds of --turn-timeout, a bad value is a usage error.
+#[cfg(feature = "simulator")]
+fn parse_turn_timeout(turn_timeout: &str) -> u64 {
+    turn_timeout.trim().parse().unwrap_or_else(|_| {
+        eprintln!("Invalid turn timeout {:?}, expected milliseconds", turn_timeout);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    })
+}
 
-        let mut input_first_line = String::new();
-        stdin.read_line(&mut input_first_line).unwrap();
-        let inputs = input_first_line.split(" ").collect::<Vec<_>>();
-        let rick_y = parse_input!(inputs[0], usize); // row where Rick is located.
-        let rick_x = parse_input!(inputs[1], usize); // column where Rick is located.
+#[cfg(all(feature = "simulator", feature = "generator"))]
+fn fuzz_target(name: &str) -> fuzz::Target {
+    fuzz::Target::from_name(name).unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    })
+}
+
+#[cfg(all(feature = "simulator", feature = "generator"))]
+fn run_fuzz(target: &str, iterations: &str, seed: &str) {
+    let targets = if target == "all" {
+        fuzz::Target::all().to_vec()
+    } else {
+        vec![fuzz_target(target)]
+    };
+    let iterations = parse_input!(iterations, usize);
+    let seed = parse_input!(seed, u64);
+
+    let mut finding_count = 0;
+    for target in targets {
+        let findings = fuzz::fuzz(target, iterations, seed);
+        println!("{}: {} findings in {} runs", target.name(), findings.len(), iterations);
+        let filenames = fuzz::save_findings(&findings, Path::new(FUZZ_FINDINGS_DIR))
+            .unwrap_or_else(|err| panic!("cannot write {}: {}", FUZZ_FINDINGS_DIR, err));
+        for (finding, filename) in findings.iter().zip(filenames) {
+            println!("    {}: {}\n        {}", finding.location, finding.message, filename);
+        }
+        finding_count += findings.len();
+    }
 
-        let rick_coord = (rick_x, rick_y).into();
+    if finding_count > 0 {
+        process::exit(1);
+    }
+}
+
+// Run a saved input without catching the panic, to debug it.
+#[cfg(all(feature = "simulator", feature = "generator"))]
+fn fuzz_repro(target: &str, input_filename: &str) {
+    let input = fs::read(input_filename)
+        .unwrap_or_else(|err| panic!("cannot read {}: {}", input_filename, err));
+    fuzz_target(target).run(&input);
+    println!("no panic");
+}
+
+#[cfg(feature = "generator")]
+fn generate(width: &str, height: &str, seed: &str, loop_ratio: &str) {
+    let mut rng = StdRng::seed_from_u64(parse_input!(seed, u64));
+    let labyrinth = Labyrinth::generate(
+        &mut rng,
+        parse_input!(width, usize),
+        parse_input!(height, usize),
+        parse_input!(loop_ratio, f64),
+    );
+    print!("{}", labyrinth.to_sample_string());
+}
+
+#[cfg(all(feature = "simulator", feature = "render"))]
+fn export(labyrinth_filename: &str, kind: &str, output: &str) {
+    let mut game_sample = load_sample(labyrinth_filename);
 
-        for y in 0..height {
-            input_board_buffer[y].clear();
-            stdin.read_line(&mut input_board_buffer[y]).unwrap();
+    let result = match kind {
+        "map-svg" => fs::write(output, render::svg::map_svg(game_sample.replay())),
+        "board-svg" => {
+            let solver = play_game(labyrinth_filename, &mut game_sample);
+            fs::write(output, render::svg::board_svg(solver.board()))
+        }
+        "trace-svg" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            fs::write(output, render::svg::trace_svg(game_sample.replay()))
+        }
+        "animated-svg" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            fs::write(output, render::svg::animated_trace_svg(game_sample.replay(), 0.2))
+        }
+        "png-frames" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            render::png::write_frames(game_sample.replay(), Path::new(output))
+                .map(|frame_count| eprintln!("{} frames written", frame_count))
+        }
+        "html" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            fs::write(
+                output,
+                render::html::replay_html(game_sample.replay(), labyrinth_filename),
+            )
+        }
+        "heatmap-svg" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            let stats = GameStats::from_replay(game_sample.replay());
+            fs::write(output, render::svg::heatmap_svg(game_sample.replay(), &stats))
+        }
+        "hex-svg" => {
+            play_game(labyrinth_filename, &mut game_sample);
+            fs::write(output, render::svg::hex_svg(game_sample.replay()))
         }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("Cannot write {}: {}", output, err);
+        process::exit(1);
+    }
+}
 
-        board.update_with(rick_coord, &input_board_buffer);
-        exploration_engine.set_square_covered(rick_coord);
+#[cfg(all(feature = "simulator", feature = "render"))]
+fn print_stats(labyrinth_filename: &str) {
+    let mut game_sample = load_sample(labyrinth_filename);
+    play_game(labyrinth_filename, &mut game_sample);
+    let stats = GameStats::from_replay(game_sample.replay());
 
-        eprintln!("{}", board);
+    println!("{}", labyrinth_filename);
+    println!("{}", stats);
+    println!("{}", render::ansi::heatmap(game_sample.replay(), &stats));
+}
 
-        rounds_counter += 1;
+// Several explorers sharing their map, see TeamGame for the number of explorers and their starting points.
+#[cfg(feature = "simulator")]
+fn play_team(labyrinth_filename: &str) {
+    let mut team_game = TeamGame::new(labyrinth_filename)
+        .unwrap_or_else(|err| exit_on_sample_error(labyrinth_filename, err));
+    let team = team_game.play();
+    eprintln!("{}", team.board());
 
-        if !returning_to_starting_point && Some(rick_coord) == board.cmd_room_coord() {
-            eprintln!("First part in {} turns", rounds_counter);
-            max_rounds_left = rounds + 1;
-            returning_to_starting_point = true;
-            target_coord = board.rick_start_coord();
+    match team_game.outcome().unwrap() {
+        Outcome::Won => println!(
+            "Won by {} explorers after {} moves",
+            team_game.starts().len(),
+            team_game.moves()
+        ),
+        outcome => {
+            println!("Game lost: {:?} after {} moves", outcome, team_game.moves());
+            process::exit(1);
         }
+    }
+}
+
+#[cfg(feature = "simulator")]
+fn play_sample(labyrinth_filename: &str) {
+    let mut game_sample = load_sample(labyrinth_filename);
+    // game_sample.debug();
+    // return;
+
+    let mut stdin = game_sample.init_data_reader();
+
+    let mut input_init_line = String::new();
+    stdin.read_line(&mut input_init_line).unwrap();
+    let settings = protocol::parse_init_line(&input_init_line).unwrap();
 
-        if recalculate_aproximate_return_path_at == Some(rick_coord) {
-            return_path_iter = None;
+    let mut solver = Solver::from_settings(&settings);
+
+    eprintln!("{}\n", solver.board());
+
+    let mut input_board_buffer: Vec<_> = (0..settings.height)
+        .map(|_| String::with_capacity(settings.width + 1))
+        .collect();
+
+    loop {
+        let mut stdin = game_sample.round_data_reader();
+
+        let mut input_first_line = String::new();
+        stdin.read_line(&mut input_first_line).unwrap();
+        let rick_coord = protocol::parse_turn_line(&input_first_line).unwrap();
+
+        for row in input_board_buffer.iter_mut() {
+            row.clear();
+            stdin.read_line(row).unwrap();
         }
 
-        let next_coord = if returning_to_starting_point {
-            if max_rounds_left == 0 {
-                eprintln!("No turns left. Game Over");
-                return;
-            }
-            max_rounds_left -= 1;
+        let next_move = solver.next_move(rick_coord, &input_board_buffer);
 
-            if Some(rick_coord) == board.rick_start_coord() {
-                break;
-            } else {
-                match &mut return_path_iter {
-                    Some(iter) => iter.next().copied(),
-                    None => {
-                        let return_path_try = a_star_algo.compute_path(
-                            &board,
-                            rick_coord,
-                            board.rick_start_coord().unwrap(),
-                            &[Content::Empty],
-                        );
-                        let return_path_to_test = if return_path_try.len() > max_rounds_left {
-                            let approximate_return_path_try = a_star_algo.compute_path(
-                                &board,
-                                rick_coord,
-                                board.rick_start_coord().unwrap(),
-                                &[Content::Empty, Content::Unknown],
-                            );
-                            if approximate_return_path_try.len() <= max_rounds_left {
-                                recalculate_aproximate_return_path_at = approximate_return_path_try
-                                    .iter()
-                                    .enumerate()
-                                    .find_map(|(i, coord)| {
-                                        if board.get_content(coord) == Content::Unknown {
-                                            // i-2 -> the unknown become visible with the radar
-                                            Some(approximate_return_path_try[i - 2])
-                                        } else {
-                                            None
-                                        }
-                                    });
-                            }
-                            approximate_return_path_try
-                        } else {
-                            return_path_try
-                        };
-                        if return_path_to_test.len() <= max_rounds_left {
-                            return_path = return_path_to_test;
-                            let mut iter = return_path.iter();
-                            let next_coord = iter.next().copied();
-                            return_path_iter = Some(iter);
-                            next_coord
-                        } else {
-                            eprintln!("Best path not found ! Try exploration one time.");
-
-                            let _search_result = exploration_engine
-                                .start_look_forward(&board, rick_coord, &target_coord, 100);
-                            Some(exploration_engine.get_next_coord(&board, rick_coord))
-                        }
-                    }
-                }
-            }
-        } else {
-            target_coord = board.cmd_room_coord();
-
-            let _search_result =
-                exploration_engine.start_look_forward(&board, rick_coord, &target_coord, 100);
-            // eprintln!("search_result = {:?}", search_result);
-            // eprintln!("path = {:?}", engine.path);
-            // eprintln!("unavailable_squares = {:?}", engine.unavailable_squares);
-            // eprintln!("rick_coord = {:?}", rick_coord);
-            Some(exploration_engine.get_next_coord(&board, rick_coord))
-        };
-
-        match next_coord {
+        eprintln!("{}", solver.board());
+
+        match next_move {
             None => {
-                break;
+                game_sample.stop();
             }
-            Some(next_coord) => {
-                // eprintln!("next_coord = {:?}, rick_coord = {:?}", next_coord, rick_coord);
-                let dir: Direction = (next_coord - rick_coord).into();
+            Some(dir) => {
                 game_sample.set_action(dir.label());
                 println!("{}", dir);
             }
         }
+
+        match game_sample.outcome() {
+            None => (),
+            Some(Outcome::Won) => {
+                eprintln!("Achieved in {} turns", solver.rounds_counter());
+                break;
+            }
+            Some(outcome) => {
+                eprintln!("Game lost: {:?}", outcome);
+                break;
+            }
+        }
     }
-    eprintln!("Achieved in {} turns", rounds_counter);
 }
@@ -1,11 +1,13 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     // fmt,
 };
 
 use crate::vect2_t::UCoord2;
+use crate::grid::Grid;
 use crate::direction::Direction;
 use crate::board::{Board,Content};
+use crate::range_helper::radar_range;
 
 #[derive(PartialEq, Debug)]
 enum SquareState {
@@ -25,6 +27,12 @@ pub struct Engine {
     path: Vec<UCoord2>,
 }
 
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Engine {
     pub fn new() -> Self {
         Self {
@@ -37,6 +45,11 @@ impl Engine {
         self.unavailable_squares.clear();
     }
 
+    // Path found by the last look forward, the next square to go is the last one.
+    pub fn path(&self) -> &[UCoord2] {
+        &self.path
+    }
+
     pub fn set_square_covered(&mut self, coord: UCoord2) {
         self.unavailable_squares.insert(coord, SquareState::Covered);
     }
@@ -102,7 +115,8 @@ impl Engine {
         let mut possible_dest_moves: Vec<_> = board
             .neighbours_in_board_iter(current_coord, first_dir)
             .filter(|&coord| {
-                board.get_content(&coord) == Content::Empty && !self.unavailable_squares.contains_key(&coord)
+                board.get_content(&coord).is_walkable(&[Content::Empty], board.keys())
+                    && !self.unavailable_squares.contains_key(&coord)
             })
             .collect();
 
@@ -143,27 +157,126 @@ impl Engine {
         }
     }
 
-    pub fn get_next_coord(&mut self, board: &Board, current_coord: UCoord2) -> UCoord2 {
+    // None when every square around is a dead end and there is no covered square to go back to.
+    pub fn get_next_coord(&mut self, board: &Board, current_coord: UCoord2) -> Option<UCoord2> {
         if self.path.is_empty() {
             let previous_covered_square = board
                 .neighbours_in_board_iter(current_coord, Direction::Left)
-                .find_map(|coord| {
-                    if self.unavailable_squares.get(&coord) == Some(&SquareState::Covered) {
-                        Some(coord)
-                    } else {
-                        None
-                    }
-                })
-                .unwrap();
+                .find(|coord| self.unavailable_squares.get(coord) == Some(&SquareState::Covered))?;
             self.unavailable_squares.insert(current_coord, SquareState::BadWay);
             self.unavailable_squares.remove(&previous_covered_square);
-            previous_covered_square
-        // match previous_covered_square {
-        //     Some(coord) => coord,
-        //     None => {break;}
-        // }
+            Some(previous_covered_square)
         } else {
-            *self.path.last().unwrap()
+            self.path.last().copied()
         }
     }
 }
+
+// Targets of two explorers closer than this are in the same corridor, the radar of one shows what the other would see.
+const FRONTIER_SPREAD: i32 = 4;
+
+// Known squares next to an unknown one : walking there shows new squares.
+pub fn frontier_squares(board: &Board) -> Vec<UCoord2> {
+    if let Some(bits) = board.exact_bits() {
+        return bits.frontier().coords().collect();
+    }
+
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| UCoord2::new(x, y)))
+        .filter(|coord| {
+            board.get_content(coord).is_walkable(&[Content::Empty], board.keys())
+                && board
+                    .neighbours_in_board_iter(*coord, Direction::Left)
+                    .any(|neighbour| board.get_content(&neighbour) == Content::Unknown)
+        })
+        .collect()
+}
+
+// Whether the radar window of the square holds an unknown square.
+pub fn reveals_unknown(board: &Board, coord: UCoord2) -> bool {
+    if let Some(bits) = board.exact_bits() {
+        return bits.any_unknown_in_window(coord);
+    }
+
+    let wraps = board.topology().wraps();
+    radar_range(coord.y, board.height(), wraps).into_iter().any(|y| {
+        radar_range(coord.x, board.width(), wraps)
+            .into_iter()
+            .any(|x| board.get_content(&UCoord2::new(x, y)) == Content::Unknown)
+    })
+}
+
+// Known squares from where the radar shows an unknown square, a word at a time with the bitboards.
+pub fn revealing_squares(board: &Board) -> Vec<UCoord2> {
+    if let Some(bits) = board.exact_bits() {
+        return bits.revealing_squares().coords().collect();
+    }
+
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| UCoord2::new(x, y)))
+        .filter(|coord| {
+            board.get_content(coord).is_walkable(&[Content::Empty], board.keys()) && reveals_unknown(board, *coord)
+        })
+        .collect()
+}
+
+// Walking distance to every known square reachable from start.
+pub fn walking_distances(board: &Board, start: UCoord2) -> Grid<Option<usize>> {
+    let mut distances = Grid::new(board.width(), board.height(), None);
+    distances[start] = Some(0);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(coord) = queue.pop_front() {
+        let distance = distances[coord].unwrap();
+        for neighbour in board.neighbours_in_board_iter(coord, Direction::Left) {
+            if board.get_content(&neighbour).is_walkable(&[Content::Empty], board.keys())
+                && distances[neighbour].is_none()
+            {
+                distances[neighbour] = Some(distance + 1);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    distances
+}
+
+// Cooperative exploration : the nearest pairs of explorer and frontier square are taken first, skipping the squares
+// close to a target already given so that two explorers don't walk the same corridor. An explorer left without
+// target then takes its nearest frontier square anyway, None when it cannot reach any.
+pub fn assign_frontier_targets(board: &Board, explorers: &[UCoord2]) -> Vec<Option<UCoord2>> {
+    let frontier = frontier_squares(board);
+    let mut pairs: Vec<(usize, usize, UCoord2)> = explorers
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, &explorer)| {
+            let distances = walking_distances(board, explorer);
+            frontier
+                .iter()
+                .filter_map(|coord| match distances[*coord] {
+                    Some(distance) if distance > 0 => Some((distance, idx, *coord)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    pairs.sort_by_key(|&(distance, idx, coord)| (distance, idx, coord.y, coord.x));
+
+    let mut targets = vec![None; explorers.len()];
+    for spread in &[true, false] {
+        for &(_, idx, coord) in &pairs {
+            let too_close = |target: &Option<UCoord2>| {
+                target.is_some_and(|target| {
+                    let v = target - coord;
+                    v.x.abs() + v.y.abs() < FRONTIER_SPREAD
+                })
+            };
+            if targets[idx].is_none() && !(*spread && targets.iter().any(too_close)) {
+                targets[idx] = Some(coord);
+            }
+        }
+    }
+
+    targets
+}
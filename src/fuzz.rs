@@ -0,0 +1,321 @@
+use std::{
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::board::Board;
+use crate::game_sample::GameSample;
+use crate::generator::Labyrinth;
+use crate::protocol;
+use crate::solver::Solver;
+
+// Turns the solver plays on each labyrinth file that parses, the fuzzer spends its time on new inputs rather than on
+// long games.
+const FUZZ_TURNS: usize = 30;
+
+// Code paths fed with untrusted text, every one of them should reject bad input without panicking.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    // Labyrinth sample file, as read by GameSample.
+    LabyrinthFile,
+    // Whole CodinGame input of a game, as read by the bot from stdin.
+    Protocol,
+    // "width height x y" then the rows given to Board::update_with.
+    UpdateWith,
+}
+
+impl Target {
+    pub fn all() -> [Self; 3] {
+        [Self::LabyrinthFile, Self::Protocol, Self::UpdateWith]
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::LabyrinthFile => "labyrinth_file",
+            Self::Protocol => "protocol",
+            Self::UpdateWith => "update_with",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|target| target.name() == name)
+    }
+
+    // Run the target on one input, panics are the findings.
+    pub fn run(&self, input: &[u8]) {
+        match self {
+            Self::LabyrinthFile => {
+                // errors are the expected answer to bad input, only panics are findings
+                let sample = String::from_utf8_lossy(input);
+                if let Ok(mut game_sample) = GameSample::from_sample_string(&sample) {
+                    if let Err(err) = game_sample.play_turns(FUZZ_TURNS) {
+                        panic!("the game of a parsed labyrinth broke: {}", err);
+                    }
+                }
+            }
+            Self::Protocol => {
+                // errors are the expected answer to bad input, only panics are findings
+                let _ = protocol::play(input, io::sink());
+            }
+            Self::UpdateWith => {
+                let text = String::from_utf8_lossy(input);
+                let mut lines = text.split('\n');
+                let header: Vec<usize> = match lines
+                    .next()
+                    .unwrap_or("")
+                    .split(' ')
+                    .map(|s| s.trim().parse())
+                    .collect()
+                {
+                    Ok(header) => header,
+                    Err(_) => return,
+                };
+                if let [width, height, x, y] = header[..] {
+                    // Kirk inside the board is a precondition, checked by protocol::read_turn.
+                    if width > 0
+                        && width <= protocol::MAX_SIDE
+                        && x < width
+                        && height > 0
+                        && height <= protocol::MAX_SIDE
+                        && y < height
+                    {
+                        let rows: Vec<String> = lines.map(|line| line.to_owned() + "\n").collect();
+                        let mut board = Board::new(width, height, 0);
+                        board.update_with((x, y).into(), &rows);
+                    }
+                }
+            }
+        }
+    }
+
+    fn seed_inputs(&self, rng: &mut StdRng) -> Vec<Vec<u8>> {
+        let mut labyrinths: Vec<String> = fs::read_dir("samples")
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| fs::read_to_string(entry.ok()?.path()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for &(width, height) in [(9, 7), (21, 11), (30, 15)].iter() {
+            labyrinths.push(Labyrinth::generate(rng, width, height, 0.2).to_sample_string());
+        }
+
+        match self {
+            Self::LabyrinthFile => labyrinths.into_iter().map(String::into_bytes).collect(),
+            Self::Protocol => labyrinths
+                .iter()
+                .map(|labyrinth| protocol_transcript(labyrinth, 5).into_bytes())
+                .collect(),
+            Self::UpdateWith => labyrinths
+                .iter()
+                .map(|labyrinth| {
+                    let mut lines = labyrinth.lines();
+                    let start: Vec<&str> = lines.next().unwrap_or("").split(' ').collect();
+                    let rows: Vec<&str> = lines.collect();
+                    format!(
+                        "{} {} {} {}\n{}",
+                        rows.first().map_or(0, |row| row.len()),
+                        rows.len(),
+                        start.first().unwrap_or(&"1"),
+                        start.get(1).unwrap_or(&"1"),
+                        rows.join("\n")
+                    )
+                    .into_bytes()
+                })
+                .collect(),
+        }
+    }
+}
+
+// What the bot would read on stdin during the first turns of a game on this labyrinth.
+fn protocol_transcript(labyrinth: &str, max_turns: usize) -> String {
+    let mut game_sample = match GameSample::from_sample_string(labyrinth) {
+        Ok(game_sample) => game_sample,
+        Err(_) => return String::new(),
+    };
+    let mut transcript = String::new();
+
+    let mut init_line = String::new();
+    game_sample
+        .init_data_reader()
+        .read_line(&mut init_line)
+        .unwrap();
+    transcript.push_str(&init_line);
+    let settings = protocol::parse_init_line(&init_line).unwrap();
+    let mut solver = Solver::from_settings(&settings);
+
+    for _ in 0..max_turns {
+        if game_sample.outcome().is_some() {
+            break;
+        }
+        let mut stdin = game_sample.round_data_reader();
+        let mut first_line = String::new();
+        stdin.read_line(&mut first_line).unwrap();
+        let mut rows = vec![String::new(); settings.height];
+        for row in rows.iter_mut() {
+            stdin.read_line(row).unwrap();
+        }
+
+        transcript.push_str(&first_line);
+        rows.iter().for_each(|row| transcript.push_str(row));
+
+        match solver.next_move(protocol::parse_turn_line(&first_line).unwrap(), &rows) {
+            Some(dir) => game_sample.set_action(dir.label()),
+            None => break,
+        }
+    }
+
+    transcript
+}
+
+const INTERESTING_BYTES: &[u8] = b"#.?TCK \n\r\t-0123456789";
+const INTERESTING_NUMBERS: &[&str] = &[
+    "0",
+    "1",
+    "2",
+    "-1",
+    "",
+    "99999999999999999999999",
+    "3.5",
+    "1e3",
+];
+
+fn mutate(rng: &mut StdRng, input: &mut Vec<u8>) {
+    for _ in 0..rng.gen_range(1, 5) {
+        let len = input.len();
+        match rng.gen_range(0, 7) {
+            0 if len > 0 => {
+                let idx = rng.gen_range(0, len);
+                input[idx] = *INTERESTING_BYTES.choose(rng).unwrap();
+            }
+            1 if len > 0 => {
+                let idx = rng.gen_range(0, len);
+                input[idx] = rng.gen();
+            }
+            2 if len > 0 => {
+                let start = rng.gen_range(0, len);
+                let end = rng.gen_range(start, len.min(start + 8) + 1);
+                input.drain(start..end);
+            }
+            3 => {
+                let idx = rng.gen_range(0, len + 1);
+                let byte = *INTERESTING_BYTES.choose(rng).unwrap();
+                input.insert(idx, byte);
+            }
+            4 if len > 0 => {
+                input.truncate(rng.gen_range(0, len));
+            }
+            5 => {
+                // drop or duplicate a whole line
+                let mut lines: Vec<Vec<u8>> =
+                    input.split(|&b| b == b'\n').map(|l| l.to_vec()).collect();
+                let idx = rng.gen_range(0, lines.len());
+                if rng.gen_bool(0.5) {
+                    lines.remove(idx);
+                } else {
+                    let line = lines[idx].clone();
+                    lines.insert(idx, line);
+                }
+                *input = lines.join(&b'\n');
+            }
+            _ => {
+                // replace a number with an interesting one
+                let numbers: Vec<(usize, usize)> = (0..input.len())
+                    .filter(|&idx| {
+                        input[idx].is_ascii_digit()
+                            && (idx == 0 || !input[idx - 1].is_ascii_digit())
+                    })
+                    .map(|idx| {
+                        let end = (idx..input.len())
+                            .find(|&end| !input[end].is_ascii_digit())
+                            .unwrap_or(input.len());
+                        (idx, end)
+                    })
+                    .collect();
+                if let Some(&(start, end)) = numbers.choose(rng) {
+                    let number = if rng.gen_bool(0.5) {
+                        INTERESTING_NUMBERS.choose(rng).unwrap().to_string()
+                    } else {
+                        rng.gen_range(0, 300).to_string()
+                    };
+                    input.splice(start..end, number.bytes());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub target: Target,
+    pub input: Vec<u8>,
+    pub message: String,
+    // file:line of the panic
+    pub location: String,
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+// Run the target on mutated seed inputs. Findings are deduplicated by panic location.
+pub fn fuzz(target: Target, iterations: usize, seed: u64) -> Vec<Finding> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let seeds = target.seed_inputs(&mut rng);
+    let mut findings: Vec<Finding> = Vec::new();
+
+    let location = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+    let hook_location = location.clone();
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Some(loc) = info.location() {
+            *hook_location.lock().unwrap() = format!("{}:{}", loc.file(), loc.line());
+        }
+    }));
+
+    for _ in 0..iterations {
+        let mut input = seeds.choose(&mut rng).unwrap().clone();
+        mutate(&mut rng, &mut input);
+
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| target.run(&input))) {
+            let location = location.lock().unwrap().clone();
+            if findings.iter().all(|finding| finding.location != location) {
+                findings.push(Finding {
+                    target,
+                    input,
+                    message: panic_message(&*payload),
+                    location,
+                });
+            }
+        }
+    }
+
+    panic::set_hook(previous_hook);
+    findings
+}
+
+// Save each finding input in dir, named after the target, returns the file names.
+pub fn save_findings(findings: &[Finding], dir: &Path) -> io::Result<Vec<String>> {
+    fs::create_dir_all(dir)?;
+    findings
+        .iter()
+        .enumerate()
+        .map(|(i, finding)| {
+            let path = dir.join(format!("{}-{:03}.txt", finding.target.name(), i));
+            fs::write(&path, &finding.input)?;
+            Ok(path.display().to_string())
+        })
+        .collect()
+}
@@ -1,112 +1,774 @@
 use std::{
-    fmt::Write,
+    error,
+    fmt::{self, Write},
     io::{self, BufRead},
     fs::File,
     str::from_utf8,
+    sync::Arc,
 };
 
 use itertools::Itertools;
 
 use crate::vect2_t::{UCoord2, UDelta2};
-use crate::range_helper::centered_range;
+use crate::grid::Grid;
+use crate::topology::{self, Topology};
+use crate::keys::{KeyColor, KeySet};
+use crate::guards::Guard;
+use crate::range_helper::radar_range;
+use crate::replay::Replay;
+use crate::protocol::{self, GameSettings, ProtocolError, MAX_SIDE};
+use crate::solver::Solver;
+use crate::stats::shortest_distance;
+
+// Kirk's jetpack allows this number of moves.
+pub const FUEL: usize = 1200;
+
+// Labyrinth files with only "x y" in their header give the alarm this share of the shortest way from the control room
+// back to the start on top of it, a bit of room for a return along the known squares only.
+const DERIVED_ROUNDS_MARGIN_PERCENT: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Won,
+    HitWall,
+    // Kirk tried to cross a door without its key.
+    LockedDoor,
+    // Kirk stepped on a hazard square `~`.
+    Hazard,
+    // Kirk and a guard met on a square or crossed each other.
+    Caught,
+    OutOfFuel,
+    AlarmExpired,
+    InvalidAction,
+    NoAction,
+    // The bot did not answer in time.
+    Timeout,
+}
+
+// How the toggling walls of a labyrinth file move: `%` squares start closed and `_` squares start open.
+// The radar shows them as `#` and `.` like the other squares.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WallEvents {
+    // The walls never move, as in the classic game.
+    Static,
+    // Every toggling wall opens or closes after this number of moves.
+    Schedule(usize),
+    // Each toggling wall opens or closes after each move with this probability.
+    Random { probability: f64, seed: u64 },
+}
+
+// Why a labyrinth file cannot be played.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleError {
+    // The header "x y rounds" with its rooms count and options cannot be parsed.
+    MalformedHeader(String),
+    NoRows,
+    WrongRowWidth {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    TooBig {
+        width: usize,
+        height: usize,
+    },
+    // The squares are ASCII characters, the radar sends them one byte each.
+    NonAsciiSquare {
+        row: usize,
+        column: usize,
+    },
+    StartOutOfBounds {
+        coord: UCoord2,
+        width: usize,
+        height: usize,
+    },
+    // Reading the file failed, invalid UTF-8 included.
+    Io(String),
+}
+
+impl fmt::Display for SampleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedHeader(line) => write!(f, "malformed header line {:?}", line),
+            Self::NoRows => write!(f, "the labyrinth has no rows"),
+            Self::WrongRowWidth {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row {} is {} squares wide instead of {}",
+                row, actual, expected
+            ),
+            Self::TooBig { width, height } => write!(
+                f,
+                "the {}x{} labyrinth is bigger than {}x{}",
+                width, height, MAX_SIDE, MAX_SIDE
+            ),
+            Self::NonAsciiSquare { row, column } => {
+                write!(f, "the square at column {} of row {} is not ASCII", column, row)
+            }
+            Self::StartOutOfBounds {
+                coord,
+                width,
+                height,
+            } => write!(
+                f,
+                "starting point {} is out of the {}x{} labyrinth",
+                coord, width, height
+            ),
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl error::Error for SampleError {}
+
+impl From<io::Error> for SampleError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+// The labyrinth of a game as it is now: the squares with the toggling walls where they stand, and the guards.
+// A team game referees all its explorers against the same world.
+pub struct World {
+    map: Grid<u8>,
+    topology: Arc<dyn Topology>,
+    wall_events: WallEvents,
+    toggling_coords: Vec<UCoord2>,
+    // patrolling guards, the map shows an empty square under them
+    guards: Vec<Guard>,
+    // moves played so far, the walls toggle after them
+    moves: usize,
+}
+
+impl World {
+    // The world of validated labyrinth rows, `%` and `_` toggling walls and guards included.
+    fn from_rows(mut rows: Vec<Vec<u8>>, topology: Arc<dyn Topology>, wall_events: WallEvents) -> Self {
+        let mut toggling_coords = Vec::new();
+        let mut guards = Vec::new();
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, square) in row.iter_mut().enumerate() {
+                let coord = UCoord2::new(x, y);
+                match *square {
+                    b'%' => *square = b'#',
+                    b'_' => *square = b'.',
+                    _ => {
+                        if let Some(guard) = Guard::from_byte(coord, *square) {
+                            guards.push(guard);
+                            *square = b'.';
+                        }
+                        continue;
+                    }
+                }
+                toggling_coords.push(coord);
+            }
+        }
+
+        Self {
+            map: Grid::from_rows(rows),
+            topology,
+            wall_events,
+            toggling_coords,
+            guards,
+            moves: 0,
+        }
+    }
+
+    pub fn map(&self) -> &Grid<u8> {
+        &self.map
+    }
+
+    pub fn topology(&self) -> &Arc<dyn Topology> {
+        &self.topology
+    }
+
+    pub fn wall_events(&self) -> WallEvents {
+        self.wall_events
+    }
+
+    pub fn guards(&self) -> &[Guard] {
+        &self.guards
+    }
+
+    // Only the moves of the topology are valid actions.
+    pub fn is_move(&self, from: UCoord2, delta: UDelta2) -> bool {
+        self.topology.deltas(from).contains(&delta)
+    }
+
+    pub fn guard_on(&self, coord: UCoord2) -> bool {
+        self.guards.iter().any(|guard| guard.coord() == coord)
+    }
+
+    // The square a move leads to, through a portal to its pair, with the content of the square stepped on.
+    // Hazards are reached like the other squares, walls and doors without their key end the game.
+    pub fn resolve_move(&self, from: UCoord2, delta: UDelta2, keys: KeySet) -> Result<(UCoord2, u8), Outcome> {
+        let map = &self.map;
+        let is_blocked = |coord: UCoord2| map.get(coord).is_none_or(|&square| square == b'#');
+        if !self.topology.allows(from, delta, &is_blocked) {
+            return Err(Outcome::HitWall);
+        }
+
+        let dest = self
+            .topology
+            .neighbour(from, delta, map.width(), map.height())
+            .ok_or(Outcome::HitWall)?;
+        match map[dest] {
+            b'#' => Err(Outcome::HitWall),
+            content if KeyColor::from_door_byte(content).is_some_and(|color| !keys.contains(color)) => {
+                Err(Outcome::LockedDoor)
+            }
+            content @ b'0'..=b'9' => Ok((self.portal_exit(dest, content), content)),
+            content => Ok((dest, content)),
+        }
+    }
+
+    fn portal_exit(&self, entry: UCoord2, portal: u8) -> UCoord2 {
+        self.map
+            .iter()
+            .find(|&(coord, &square)| square == portal && coord != entry)
+            .map_or(entry, |(coord, _)| coord)
+    }
+
+    // Guards step after the explorers, then the walls due after this move toggle. A wall never closes on an
+    // explorer.
+    pub fn advance(&mut self, explorers: &[UCoord2]) {
+        self.moves += 1;
+
+        let map = &self.map;
+        let is_open = |coord: UCoord2| {
+            matches!(map.get(coord), Some(b'.') | Some(b'C') | Some(b'T'))
+        };
+        for guard in self.guards.iter_mut() {
+            *guard = guard.step(is_open);
+        }
+
+        for &coord in &self.toggling_coords {
+            let toggle = match self.wall_events {
+                WallEvents::Static => false,
+                WallEvents::Schedule(period) => period > 0 && self.moves.is_multiple_of(period),
+                WallEvents::Random { probability, seed } => {
+                    let draw = mix(seed ^ mix(self.moves as u64) ^ mix(((coord.y as u64) << 32) | coord.x as u64));
+                    (draw >> 11) as f64 / (1u64 << 53) as f64 <= probability
+                }
+            };
+
+            let square = &mut self.map[coord];
+            match *square {
+                b'#' if toggle => *square = b'.',
+                b'.' if toggle && !explorers.contains(&coord) => *square = b'#',
+                _ => (),
+            }
+        }
+    }
+}
+
+// One explorer of the world, with the radar view the bot is sent and the keys picked up.
+pub struct Explorer {
+    start: UCoord2,
+    current: UCoord2,
+    // keys picked up, they stay on the map
+    keys: KeySet,
+    view: Grid<u8>,
+    // squares of the view where Kirk and the guards were drawn, with what the bot knew of them
+    drawn: Vec<(UCoord2, u8)>,
+}
+
+impl Explorer {
+    pub fn new(world: &World, start: UCoord2) -> Self {
+        Self {
+            start,
+            current: start,
+            keys: KeySet::new(),
+            view: Grid::new(world.map.width(), world.map.height(), b'?'),
+            drawn: Vec::new(),
+        }
+    }
+
+    pub fn start(&self) -> UCoord2 {
+        self.start
+    }
+
+    pub fn current(&self) -> UCoord2 {
+        self.current
+    }
+
+    pub fn keys(&self) -> KeySet {
+        self.keys
+    }
+
+    pub fn view(&self) -> &Grid<u8> {
+        &self.view
+    }
+
+    // Move through the world, picking up the key stepped on. Returns the content of the square reached, or the
+    // outcome of a move ending the game, on a hazard square for Hazard.
+    pub fn step(&mut self, world: &World, delta: UDelta2) -> Result<u8, Outcome> {
+        let (dest, content) = world.resolve_move(self.current, delta, self.keys)?;
+        self.current = dest;
+        if content == b'~' {
+            return Err(Outcome::Hazard);
+        }
+        if let Some(color) = KeyColor::from_key_byte(content) {
+            self.keys.insert(color);
+        }
+        Ok(content)
+    }
+
+    // Returns the squares seen for the first time.
+    pub fn update_view(&mut self, world: &World) -> Vec<UCoord2> {
+        let (width, height) = (world.map.width(), world.map.height());
+        let y_range = radar_range(self.current.y, height, world.topology.wraps());
+        let x_range = radar_range(self.current.x, width, world.topology.wraps());
+
+        // Kirk and the guards are only where they are now, out of the radar window the bot keeps what it saw
+        for (coord, square) in self.drawn.drain(..).rev() {
+            self.view[coord] = square;
+        }
+
+        let mut revealed = Vec::new();
+
+        for (coord, &square) in world.map.window(&x_range, &y_range) {
+            if self.view[coord] == b'?' {
+                revealed.push(coord);
+            }
+            self.view[coord] = square;
+        }
+
+        // guards are only seen inside the radar window
+        for guard in &world.guards {
+            let coord = guard.coord();
+            if y_range.contains(&coord.y) && x_range.contains(&coord.x) {
+                self.drawn.push((coord, self.view[coord]));
+                self.view[coord] = guard.byte();
+            }
+        }
+
+        self.drawn.push((self.current, self.view[self.current]));
+        self.view[self.current] = b'K';
+
+        revealed
+    }
+}
 
 // GameSample simulate input from game data stored in file. It's for testing purpose, to use its own labyrinths.
+// It also referees the game with the CodinGame rules.
 pub struct GameSample {
-    orig_board_rows_data: Vec<Vec<u8>>,
-    view: Vec<Vec<u8>>,
+    world: World,
+    explorer: Explorer,
     width: usize,
     height: usize,
-    start: UCoord2,
-    rounds: i32,
-    current: UCoord2,
+    rounds: usize,
+    replay: Replay,
+    fuel_left: usize,
+    alarm_rounds_left: Option<usize>,
+    outcome: Option<Outcome>,
+    cmd_rooms_to_visit: usize,
+    visited_cmd_rooms: Vec<UCoord2>,
 }
 
 impl GameSample {
-    pub fn new(filename: &str) -> Self {
-        let mut board_rows_data = Vec::new();
+    pub fn new(filename: &str) -> Result<Self, SampleError> {
+        let file = File::open(filename)?;
+        Self::from_reader(io::BufReader::new(file))
+    }
+
+    // Same as new, with the content of a labyrinth file.
+    pub fn from_sample_string(sample: &str) -> Result<Self, SampleError> {
+        Self::from_reader(sample.as_bytes())
+    }
 
-        let file = File::open(filename).unwrap();
-        let mut reader = io::BufReader::new(file);
+    fn from_reader<R: BufRead>(mut reader: R) -> Result<Self, SampleError> {
+        let mut board_rows_data = Vec::new();
         let mut input_line = String::new();
 
-        reader.read_line(&mut input_line).unwrap();
+        reader.read_line(&mut input_line)?;
+        let malformed = || SampleError::MalformedHeader(input_line.trim_end().to_owned());
 
-        let (kc, kr, rounds) = match input_line
-            .trim()
-            .split(' ')
-            .map(|s| s.parse::<i32>().unwrap())
-            .collect::<Vec<_>>()
-            .as_slice()
-        {
-            &[kc, kr, rounds] => (kc, kr, rounds),
-            _ => panic!(),
+        // "x y rounds", or "x y" with the rounds derived from the map, then for the multiple rooms variant the number of C rooms to visit, or "all",
+        // and the options of the changing labyrinths: "toggle=<period>" or "random=<probability> [seed=<seed>]"
+        // and of the other grids and movement models: "grid=<hex|torus>" or "moves=<4|8|8cut|knight|dx:dy,...>"
+        let (options, header): (Vec<_>, Vec<_>) = input_line
+            .split_whitespace()
+            .partition(|value| value.contains('='));
+        let numbers = header
+            .iter()
+            .take(3)
+            .map(|s| s.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| malformed())?;
+        let (kc, kr, rounds) = match *numbers.as_slice() {
+            [kc, kr, rounds] => (kc, kr, Some(rounds)),
+            [kc, kr] => (kc, kr, None),
+            _ => return Err(malformed()),
         };
 
-        let start: UCoord2 = (kc, kr).into();
+        let start = UCoord2::new(kc, kr);
 
         loop {
             let mut input_line = String::new();
-            if reader.read_line(&mut input_line).unwrap() == 0 {
+            if reader.read_line(&mut input_line)? == 0 {
                 break;
             }
-            board_rows_data.push(input_line.trim().as_bytes().to_vec());
+            // the squares of hex maps are written apart, odd rows shifted by one space
+            board_rows_data.push(input_line.bytes().filter(|byte| !byte.is_ascii_whitespace()).collect::<Vec<_>>());
         }
 
+        // an editor's newlines after the last row are not rows
+        while board_rows_data.last().is_some_and(Vec::is_empty) {
+            board_rows_data.pop();
+        }
+
+        let wall_events = parse_wall_events(&options).ok_or_else(malformed)?;
+
+        if let Some((row, column)) = board_rows_data
+            .iter()
+            .enumerate()
+            .find_map(|(row, bytes)| bytes.iter().position(|byte| !byte.is_ascii()).map(|column| (row, column)))
+        {
+            return Err(SampleError::NonAsciiSquare { row, column });
+        }
+
+        // the first row that is not empty gives the width, an empty row before it is a row of the wrong width
+        let width = board_rows_data
+            .iter()
+            .map(Vec::len)
+            .find(|&len| len > 0)
+            .ok_or(SampleError::NoRows)?;
         let height = board_rows_data.len();
-        let width = board_rows_data[0].len();
+        if let Some((row, actual)) = board_rows_data
+            .iter()
+            .map(Vec::len)
+            .enumerate()
+            .find(|&(_, len)| len != width)
+        {
+            return Err(SampleError::WrongRowWidth {
+                row,
+                expected: width,
+                actual,
+            });
+        }
+        if width > MAX_SIDE || height > MAX_SIDE {
+            return Err(SampleError::TooBig { width, height });
+        }
+        if kc >= width || kr >= height {
+            return Err(SampleError::StartOutOfBounds {
+                coord: start,
+                width,
+                height,
+            });
+        }
+        // the first files marked the start with Kirk's K, as the radar shows him
+        if board_rows_data[kr][kc] == b'K' {
+            board_rows_data[kr][kc] = b'T';
+        }
+        let topology = topology::from_options(option(&options, "grid"), option(&options, "moves"), width, height)
+            .ok_or_else(malformed)?;
+        let world = World::from_rows(board_rows_data, Arc::clone(&topology), wall_events);
 
-        Self {
-            orig_board_rows_data: board_rows_data,
-            view: vec![vec![b'?'; width]; height],
+        // a rooms count asks for at least one control room to visit, and no more than the map holds
+        let map_cmd_rooms = world.map.iter().filter(|&(_, &c)| c == b'C').count();
+        let cmd_rooms = match header.get(3) {
+            None => 1,
+            Some(&"all") => map_cmd_rooms,
+            Some(count) => count.parse().map_err(|_| malformed())?,
+        };
+        if header.len() > 3 && (cmd_rooms == 0 || cmd_rooms > map_cmd_rooms) {
+            return Err(malformed());
+        }
+
+        let rounds = match rounds {
+            Some(rounds) => rounds,
+            None => derived_rounds(&world, start).ok_or_else(malformed)?,
+        };
+
+        let mut replay = Replay::new(world.map.clone(), start, rounds);
+        replay.set_cmd_rooms_to_visit(cmd_rooms);
+        replay.set_topology(Arc::clone(&topology));
+
+        Ok(Self {
+            explorer: Explorer::new(&world, start),
+            world,
             width,
             height,
-            start,
             rounds,
-            current: start,
-        }
+            replay,
+            fuel_left: FUEL,
+            alarm_rounds_left: None,
+            outcome: None,
+            cmd_rooms_to_visit: cmd_rooms,
+            visited_cmd_rooms: Vec::new(),
+        })
     }
 
-    pub fn debug(&mut self) {
-        self.update_view();
-        println!("{}", self.orig_board_rows_data.iter().map(|v| from_utf8(v).unwrap()).intersperse("\n").collect::<String>());
-        println!("");
-        println!("{}", self.view.iter().map(|v| from_utf8(v).unwrap()).intersperse("\n").collect::<String>());
-        println!("");
+    pub fn wall_events(&self) -> WallEvents {
+        self.world.wall_events
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
 
+    pub fn fuel_left(&self) -> usize {
+        self.fuel_left
     }
 
-    fn update_view(&mut self) {
-        let y_range = centered_range(self.current.y, self.height);
-        let x_range = centered_range(self.current.x, self.width);
+    // Keys picked up by Kirk, they stay on the map.
+    pub fn keys(&self) -> KeySet {
+        self.explorer.keys
+    }
 
-        for y in y_range {
-            self.view[y][x_range.clone()].copy_from_slice(&self.orig_board_rows_data[y][x_range.clone()]);
+    pub fn guards(&self) -> &[Guard] {
+        &self.world.guards
+    }
+
+    pub fn topology(&self) -> &Arc<dyn Topology> {
+        &self.world.topology
+    }
+
+    pub fn current(&self) -> UCoord2 {
+        self.explorer.current
+    }
+
+    pub fn start(&self) -> UCoord2 {
+        self.explorer.start
+    }
+
+    // The world of the game, for a team of explorers to play it.
+    pub fn into_world(self) -> World {
+        self.world
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub fn settings(&self) -> GameSettings {
+        GameSettings {
+            height: self.height,
+            width: self.width,
+            rounds: self.rounds,
+            cmd_rooms: self.cmd_rooms_to_visit,
         }
+    }
+
+    // Play a whole game with the solver, through the same line protocol as the real game.
+    // The game is recorded in the replay, the solver is returned to inspect its final knowledge.
+    pub fn play(&mut self) -> Result<Solver, ProtocolError> {
+        self.play_turns(usize::MAX)
+    }
+
+    // The same for at most this number of turns, the game is left without outcome when they are not enough.
+    pub fn play_turns(&mut self, max_turns: usize) -> Result<Solver, ProtocolError> {
+        let mut stdin = self.init_data_reader();
+
+        let mut input_init_line = String::new();
+        stdin.read_line(&mut input_init_line)?;
+        let settings = protocol::parse_init_line(&input_init_line)?;
+
+        let mut solver = Solver::from_settings(&settings);
+        solver.set_topology(Arc::clone(&self.world.topology));
+
+        let mut input_board_buffer: Vec<_> = (0..settings.height)
+            .map(|_| String::with_capacity(settings.width + 1))
+            .collect();
+
+        for _ in 0..max_turns {
+            let mut stdin = self.round_data_reader();
+
+            let mut input_first_line = String::new();
+            stdin.read_line(&mut input_first_line)?;
+            let rick_coord = protocol::parse_turn_line(&input_first_line)?;
+
+            for row in input_board_buffer.iter_mut() {
+                row.clear();
+                stdin.read_line(row)?;
+            }
+
+            let next_step = solver.next_step(rick_coord, &input_board_buffer);
+            self.replay
+                .set_last_decision(solver.planned_path().to_vec(), solver.is_backtracking());
+
+            match next_step {
+                Some(delta) => self.set_action(&self.world.topology.label(self.explorer.current, delta)),
+                None => self.stop(),
+            }
+
+            if self.outcome.is_some() {
+                break;
+            }
+        }
+
+        Ok(solver)
+    }
+
+    pub fn debug(&mut self) {
+        self.explorer.update_view(&self.world);
+        println!("{}", Itertools::intersperse(self.world.map.rows().map(|v| from_utf8(v).unwrap()), "\n").collect::<String>());
+        println!();
+        println!("{}", Itertools::intersperse(self.explorer.view.rows().map(|v| from_utf8(v).unwrap()), "\n").collect::<String>());
+        println!();
 
-        self.view[self.current.y][self.current.x] = b'K';
     }
 
     pub fn set_action(&mut self, action: &str) {
-        let delta: UDelta2 = match action {
-            "RIGHT" => (1, 0).into(),
-            "DOWN" => (0, 1).into(),
-            "LEFT" => (-1, 0).into(),
-            "UP" => (0, -1).into(),
-            _ => panic!(),
+        if self.outcome.is_some() {
+            return;
+        }
+
+        let current = self.explorer.current;
+        let delta = match self.world.topology.parse_label(current, action.trim()) {
+            Some(delta) if self.world.is_move(current, delta) => delta,
+            _ => {
+                self.outcome = Some(Outcome::InvalidAction);
+                return;
+            }
         };
-        self.current = self.current + delta;
+
+        self.fuel_left -= 1;
+
+        let content = match self.explorer.step(&self.world, delta) {
+            Ok(content) => content,
+            Err(outcome) => {
+                if outcome == Outcome::Hazard {
+                    self.replay.set_last_action(delta);
+                }
+                self.outcome = Some(outcome);
+                return;
+            }
+        };
+        self.replay.set_last_action(delta);
+        let dest = self.explorer.current;
+
+        if content == b'C' && !self.visited_cmd_rooms.contains(&dest) {
+            self.visited_cmd_rooms.push(dest);
+        }
+
+        match &mut self.alarm_rounds_left {
+            // the alarm is triggered by the last control room to visit
+            None if content == b'C'
+                && self.visited_cmd_rooms.len() == self.cmd_rooms_to_visit =>
+            {
+                self.alarm_rounds_left = Some(self.rounds)
+            }
+            None => (),
+            Some(rounds_left) => {
+                *rounds_left = rounds_left.saturating_sub(1);
+                if dest == self.explorer.start {
+                    self.outcome = Some(Outcome::Won);
+                } else if *rounds_left == 0 {
+                    self.outcome = Some(Outcome::AlarmExpired);
+                }
+            }
+        }
+
+        if self.outcome.is_none() && self.fuel_left == 0 {
+            self.outcome = Some(Outcome::OutOfFuel);
+        }
+
+        self.advance_world();
+    }
+
+    // Kirk is caught on a guard's square, crossing a guard included, or when a guard reaches his square.
+    fn advance_world(&mut self) {
+        let current = self.explorer.current;
+        if self.outcome.is_none() && self.world.guard_on(current) {
+            self.outcome = Some(Outcome::Caught);
+            return;
+        }
+
+        self.world.advance(&[current]);
+
+        if self.outcome.is_none() && self.world.guard_on(current) {
+            self.outcome = Some(Outcome::Caught);
+        }
+    }
+
+    // The bot gave up without winning.
+    pub fn stop(&mut self) {
+        if self.outcome.is_none() {
+            self.outcome = Some(Outcome::NoAction);
+        }
+    }
+
+    pub fn time_out(&mut self) {
+        if self.outcome.is_none() {
+            self.outcome = Some(Outcome::Timeout);
+        }
+    }
+
+    // The init line, as sent to a bot over a stream.
+    pub fn init_data(&mut self) -> String {
+        let mut data = String::new();
+        self.init_data_reader().read_line(&mut data).unwrap();
+        data
     }
 
-    pub fn init_data_reader(&mut self) -> GameSampleStdin {
+    // The turn line and the rows of the next turn, as sent to a bot over a stream.
+    pub fn round_data(&mut self) -> String {
+        let line_count = self.height + 1;
+        let mut stdin = self.round_data_reader();
+        let mut data = String::new();
+        for _ in 0..line_count {
+            let mut line = String::new();
+            stdin.read_line(&mut line).unwrap();
+            data.push_str(&line);
+        }
+        data
+    }
+
+    pub fn init_data_reader(&mut self) -> GameSampleStdin<'_> {
         GameSampleStdin::new(self, true)
     }
-    pub fn round_data_reader(&mut self) -> GameSampleStdin {
-        self.update_view();
+    pub fn round_data_reader(&mut self) -> GameSampleStdin<'_> {
+        let revealed = self.explorer.update_view(&self.world);
+        self.replay.push_turn(self.explorer.current, revealed);
 
         GameSampleStdin::new(self, false)
     }
 }
 
+// Value of a "name=value" option of the header.
+fn option<'a>(options: &[&'a str], name: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .find_map(|option| option.strip_prefix(name)?.strip_prefix('='))
+}
+
+// Shortest way back to the start from the farthest control room, plus the margin. None without a control room from
+// where the start can be reached.
+fn derived_rounds(world: &World, start: UCoord2) -> Option<usize> {
+    world
+        .map
+        .iter()
+        .filter(|&(_, &c)| c == b'C')
+        .map(|(coord, _)| shortest_distance(&world.map, world.topology.as_ref(), coord, KeySet::new(), start))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .max()
+        .map(|distance| distance + distance * DERIVED_ROUNDS_MARGIN_PERCENT / 100)
+}
+
+fn parse_wall_events(options: &[&str]) -> Option<WallEvents> {
+    match (option(options, "toggle"), option(options, "random")) {
+        (Some(period), _) => Some(WallEvents::Schedule(period.parse().ok()?)),
+        (None, Some(probability)) => Some(WallEvents::Random {
+            probability: probability.parse().ok()?,
+            seed: option(options, "seed").map_or(Some(0), |seed| seed.parse().ok())?,
+        }),
+        (None, None) => Some(WallEvents::Static),
+    }
+}
+
+// splitmix64 finalizer, enough randomness for the wall events without a dependency.
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 pub struct GameSampleStdin<'a> {
     source: &'a GameSample,
     for_init: bool,
@@ -127,11 +789,7 @@ impl<'a> GameSampleStdin<'a> {
             self.line_counter += 1;
 
             let result = if self.line_counter == 1 {
-                write!(
-                    buf,
-                    "{} {} {}\n",
-                    self.source.height, self.source.width, self.source.rounds
-                ).unwrap();
+                writeln!(buf, "{}", self.source.settings()).unwrap();
                 buf.len()
             } else {
                 0
@@ -146,9 +804,9 @@ impl<'a> GameSampleStdin<'a> {
         self.line_counter += 1;
 
         if self.line_counter == 1 {
-            write!(buf, "{} {}\n", self.source.current.y, self.source.current.x).unwrap();
+            writeln!(buf, "{} {}", self.source.explorer.current.y, self.source.explorer.current.x).unwrap();
         } else if self.line_counter >= 2 && self.line_counter <= self.source.height + 2 {
-            *buf = from_utf8(&self.source.view[self.line_counter - 2])
+            *buf = from_utf8(&self.source.explorer.view[self.line_counter - 2])
                 .unwrap()
                 .to_owned() + "\n";
         } else {
@@ -0,0 +1,134 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::direction::Direction;
+use crate::grid::Grid;
+use crate::keys::KeySet;
+use crate::movement::Movement;
+use crate::stats::shortest_distance;
+use crate::vect2_t::UCoord2;
+
+// Randomly generated labyrinth, in the same shape as a sample file.
+#[derive(Debug, Clone)]
+pub struct Labyrinth {
+    pub rows: Vec<Vec<u8>>,
+    pub start: UCoord2,
+    pub cmd_room: UCoord2,
+    pub rounds: usize,
+}
+
+impl Labyrinth {
+    // Perfect maze dug with a randomized depth first search, then `loop_ratio` of the inner walls
+    // between two corridors are removed to create cycles. Sizes are rounded down to odd numbers.
+    // The alarm rounds are the shortest distance between C and T, as in the CodinGame labyrinths.
+    pub fn generate<R: Rng>(rng: &mut R, width: usize, height: usize, loop_ratio: f64) -> Self {
+        let width = (width.max(5) - 1) | 1;
+        let height = (height.max(5) - 1) | 1;
+
+        let mut rows = vec![vec![b'#'; width]; height];
+
+        let first = UCoord2::new(1, 1);
+        rows[first.y][first.x] = b'.';
+        let mut stack = vec![first];
+
+        while let Some(&coord) = stack.last() {
+            let mut dirs = [
+                Direction::Right,
+                Direction::Down,
+                Direction::Left,
+                Direction::Up,
+            ];
+            dirs.shuffle(rng);
+
+            let next = dirs.iter().find_map(|&dir| {
+                let delta = dir.to_delta();
+                let x = coord.x as i32 + 2 * delta.x;
+                let y = coord.y as i32 + 2 * delta.y;
+                if x > 0 && y > 0 && (x as usize) < width - 1 && (y as usize) < height - 1 {
+                    let dest = UCoord2::new(x as usize, y as usize);
+                    if rows[dest.y][dest.x] == b'#' {
+                        return Some((coord.get_neighbour(&dir), dest));
+                    }
+                }
+                None
+            });
+
+            match next {
+                Some((wall, dest)) => {
+                    rows[wall.y][wall.x] = b'.';
+                    rows[dest.y][dest.x] = b'.';
+                    stack.push(dest);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        for (y, row) in rows.iter_mut().enumerate().take(height - 1).skip(1) {
+            for (x, square) in row.iter_mut().enumerate().take(width - 1).skip(1) {
+                let between_corridors = (x % 2 == 0 && y % 2 == 1) || (x % 2 == 1 && y % 2 == 0);
+                if *square == b'#' && between_corridors && rng.gen_bool(loop_ratio) {
+                    *square = b'.';
+                }
+            }
+        }
+
+        let free_squares: Vec<UCoord2> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| UCoord2::new(x, y)))
+            .filter(|coord| rows[coord.y][coord.x] == b'.')
+            .collect();
+
+        let start = *free_squares.choose(rng).unwrap();
+        let cmd_room = loop {
+            let coord = *free_squares.choose(rng).unwrap();
+            if coord != start || free_squares.len() == 1 {
+                break coord;
+            }
+        };
+
+        rows[start.y][start.x] = b'T';
+        rows[cmd_room.y][cmd_room.x] = b'C';
+
+        let rounds = shortest_distance(
+            &Grid::from_rows(rows.clone()),
+            &Movement::orthogonal(),
+            cmd_room,
+            KeySet::new(),
+            start,
+        )
+        .unwrap();
+
+        Self {
+            rows,
+            start,
+            cmd_room,
+            rounds,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    // Rows as the game sends them every round (with everything visible).
+    pub fn rows_as_strings(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|row| String::from_utf8_lossy(row).into_owned())
+            .collect()
+    }
+
+    // Content of a sample file : "x y rounds" then the map.
+    pub fn to_sample_string(&self) -> String {
+        let mut sample = format!("{} {} {}\n", self.start.x, self.start.y, self.rounds);
+        for row in self.rows_as_strings() {
+            sample.push_str(&row);
+            sample.push('\n');
+        }
+        sample
+    }
+}
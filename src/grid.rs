@@ -0,0 +1,161 @@
+use std::ops::{Index, IndexMut};
+
+use crate::vect2_t::{Bounds, UCoord2, UDelta2};
+
+// Grid of squares stored row after row in one vector, indexed by UCoord2 or, row by row, by `grid[y][x]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        self.rows().map(<[T]>::to_vec).collect()
+    }
+}
+
+impl<T> Grid<T> {
+    // Rows of the same length, the width is the one of the first row.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "rows of different lengths"
+        );
+        Self {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.width, self.height)
+    }
+
+    pub fn get(&self, coord: UCoord2) -> Option<&T> {
+        self.index_of(coord).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, coord: UCoord2) -> Option<&mut T> {
+        self.index_of(coord).map(move |idx| &mut self.cells[idx])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        self.rows().map(move |row| &row[x])
+    }
+
+    // Every square with its coordinates, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (UCoord2, &T)> + '_ {
+        self.bounds().coords().zip(&self.cells)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (UCoord2, &mut T)> + '_ {
+        self.bounds().coords().zip(&mut self.cells)
+    }
+
+    // Squares of the window of the given ranges of rows and columns, as the radar shows them.
+    pub fn window<'a>(
+        &'a self,
+        x_range: &'a [usize],
+        y_range: &'a [usize],
+    ) -> impl Iterator<Item = (UCoord2, &'a T)> + 'a {
+        y_range.iter().flat_map(move |&y| {
+            x_range
+                .iter()
+                .map(move |&x| ((x, y).into(), &self[y][x]))
+        })
+    }
+
+    // Squares reached from coord with the deltas, without the ones off the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        coord: UCoord2,
+        deltas: &'a [UDelta2],
+    ) -> impl Iterator<Item = (UCoord2, &'a T)> + 'a {
+        deltas.iter().filter_map(move |&delta| {
+            let neighbour = coord.neighbour_in(delta, self.bounds())?;
+            Some((neighbour, &self[neighbour]))
+        })
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    // Pairs of the squares of two grids of the same size.
+    pub fn zip<'a, U>(&'a self, other: &'a Grid<U>) -> Grid<(&'a T, &'a U)> {
+        assert_eq!(self.bounds(), other.bounds(), "grids of different sizes");
+        Grid {
+            cells: self.cells.iter().zip(&other.cells).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    fn index_of(&self, coord: UCoord2) -> Option<usize> {
+        if self.bounds().contains(coord) {
+            Some(coord.y * self.width + coord.x)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Index<UCoord2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: UCoord2) -> &T {
+        &self[coord.y][coord.x]
+    }
+}
+
+impl<T> IndexMut<UCoord2> for Grid<T> {
+    fn index_mut(&mut self, coord: UCoord2) -> &mut T {
+        &mut self[coord.y][coord.x]
+    }
+}
+
+// The row y.
+impl<T> Index<usize> for Grid<T> {
+    type Output = [T];
+
+    fn index(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {} out of the grid", y);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(y < self.height, "row {} out of the grid", y);
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+}
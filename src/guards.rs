@@ -0,0 +1,95 @@
+use crate::direction::Direction;
+use crate::vect2_t::UCoord2;
+
+// A guard walks straight ahead one square per turn and turns back when the square ahead is not open.
+// In the labyrinth files a guard is shown by the direction it walks: `>`, `<`, `^` or `v`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guard {
+    coord: UCoord2,
+    dir: Direction,
+}
+
+impl Guard {
+    pub fn new(coord: UCoord2, dir: Direction) -> Self {
+        Self { coord, dir }
+    }
+
+    pub fn from_byte(coord: UCoord2, byte: u8) -> Option<Self> {
+        let dir = match byte {
+            b'>' => Direction::Right,
+            b'<' => Direction::Left,
+            b'^' => Direction::Up,
+            b'v' => Direction::Down,
+            _ => return None,
+        };
+        Some(Self::new(coord, dir))
+    }
+
+    pub fn byte(&self) -> u8 {
+        match self.dir {
+            Direction::Right => b'>',
+            Direction::Left => b'<',
+            Direction::Up => b'^',
+            Direction::Down => b'v',
+        }
+    }
+
+    pub fn coord(&self) -> UCoord2 {
+        self.coord
+    }
+
+    pub fn dir(&self) -> Direction {
+        self.dir
+    }
+
+    // The guard one turn later. It stays in place, turned back, when both ways are closed.
+    pub fn step(self, is_open: impl Fn(UCoord2) -> bool) -> Self {
+        let open_neighbour = |dir: Direction| {
+            self.coord
+                .checked_add_delta(dir.to_delta())
+                .filter(|&coord| is_open(coord))
+        };
+        if let Some(ahead) = open_neighbour(self.dir) {
+            return Self::new(ahead, self.dir);
+        }
+
+        let dir = self.dir.reverse();
+        match open_neighbour(dir) {
+            Some(behind) => Self::new(behind, dir),
+            None => Self::new(self.coord, dir),
+        }
+    }
+}
+
+// Squares of the guards for the next turns, assuming they keep their patrol.
+pub struct Forecast {
+    positions: Vec<Vec<UCoord2>>,
+}
+
+impl Forecast {
+    pub fn new(guards: &[Guard], turns: usize, is_open: impl Fn(UCoord2) -> bool) -> Self {
+        let mut guards = guards.to_vec();
+        let mut positions = vec![guards.iter().map(Guard::coord).collect::<Vec<_>>()];
+        for _ in 0..turns {
+            guards = guards.iter().map(|guard| guard.step(&is_open)).collect();
+            positions.push(guards.iter().map(Guard::coord).collect());
+        }
+        Self { positions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions[0].is_empty()
+    }
+
+    pub fn turns(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    // Kirk is caught stepping on a guard, or when a guard steps on Kirk right after.
+    // Beyond the forecast every square is assumed safe.
+    pub fn is_safe(&self, coord: UCoord2, turn: usize) -> bool {
+        turn == 0
+            || turn > self.turns()
+            || !(self.positions[turn - 1].contains(&coord) || self.positions[turn].contains(&coord))
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+// Colour shared by a door and the key opening it.
+// In the labyrinth files a key is the lowercase initial of its colour, a door the uppercase one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl KeyColor {
+    pub const ALL: [KeyColor; 4] = [
+        KeyColor::Red,
+        KeyColor::Green,
+        KeyColor::Blue,
+        KeyColor::Yellow,
+    ];
+
+    pub fn key_byte(self) -> u8 {
+        match self {
+            KeyColor::Red => b'r',
+            KeyColor::Green => b'g',
+            KeyColor::Blue => b'b',
+            KeyColor::Yellow => b'y',
+        }
+    }
+
+    pub fn door_byte(self) -> u8 {
+        self.key_byte().to_ascii_uppercase()
+    }
+
+    pub fn from_key_byte(byte: u8) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|color| color.key_byte() == byte)
+    }
+
+    pub fn from_door_byte(byte: u8) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|color| color.door_byte() == byte)
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+// Keys held by Kirk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct KeySet(u8);
+
+impl KeySet {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn contains(self, color: KeyColor) -> bool {
+        self.0 & color.bit() != 0
+    }
+
+    pub fn with(self, color: KeyColor) -> Self {
+        Self(self.0 | color.bit())
+    }
+
+    pub fn insert(&mut self, color: KeyColor) {
+        *self = self.with(color);
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for KeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for color in KeyColor::ALL.iter().filter(|&&color| self.contains(color)) {
+            write!(f, "{}", color.key_byte() as char)?;
+        }
+        Ok(())
+    }
+}
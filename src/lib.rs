@@ -0,0 +1,38 @@
+pub mod range_helper;
+pub mod vect2_t;
+pub mod grid;
+pub mod bitboard;
+pub mod direction;
+pub mod movement;
+pub mod topology;
+pub mod keys;
+pub mod guards;
+pub mod a_star_algo;
+pub mod board;
+pub mod exploration;
+pub mod tour;
+pub mod solver;
+pub mod protocol;
+#[cfg(feature = "simulator")]
+pub mod team;
+#[cfg(any(feature = "simulator", feature = "render"))]
+pub mod replay;
+// the statistics of the replays, and the distances the generator gives the alarm with
+#[cfg(any(feature = "simulator", feature = "render", feature = "generator"))]
+pub mod stats;
+#[cfg(feature = "simulator")]
+pub mod game_sample;
+#[cfg(feature = "simulator")]
+pub mod team_game;
+#[cfg(feature = "simulator")]
+pub mod referee;
+#[cfg(feature = "simulator")]
+pub mod server;
+#[cfg(feature = "generator")]
+pub mod generator;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(all(feature = "simulator", feature = "generator"))]
+pub mod fuzz;
+#[cfg(feature = "cli")]
+pub mod bundle;
@@ -0,0 +1,184 @@
+use crate::direction::Direction;
+use crate::vect2_t::{UCoord2, UDelta2};
+
+const DIAGONALS: [(i32, i32); 4] = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+const KNIGHT_JUMPS: [(i32, i32); 8] = [
+    (-2, -1),
+    (-1, -2),
+    (1, -2),
+    (2, -1),
+    (2, 1),
+    (1, 2),
+    (-1, 2),
+    (-2, 1),
+];
+
+// The moves Kirk can make in one turn. The CodinGame game only has the four orthogonal ones, the other models are
+// variants of the labyrinth files, chosen with the header option `moves=<4|8|8cut|knight|dx:dy,...>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Movement {
+    deltas: Vec<UDelta2>,
+    // a diagonal move may pass beside one wall, never between two
+    corner_cutting: bool,
+}
+
+impl Default for Movement {
+    fn default() -> Self {
+        Self::orthogonal()
+    }
+}
+
+impl Movement {
+    // Starting with Left, the order the planners have always tried the neighbours in.
+    pub fn orthogonal() -> Self {
+        Self::custom(
+            Direction::Left
+                .successors()
+                .take(4)
+                .map(Direction::to_delta)
+                .collect(),
+        )
+    }
+
+    // The four orthogonal moves and the four diagonal ones. Without corner cutting a diagonal move needs both
+    // orthogonal squares beside it open.
+    pub fn eight_directions(corner_cutting: bool) -> Self {
+        let mut movement = Self::orthogonal();
+        movement.deltas.extend(DIAGONALS.iter().map(UDelta2::from));
+        movement.corner_cutting = corner_cutting;
+        movement
+    }
+
+    // The jumps of a chess knight, whatever is on the squares jumped over.
+    pub fn knight() -> Self {
+        Self::custom(KNIGHT_JUMPS.iter().map(UDelta2::from).collect())
+    }
+
+    pub fn custom(deltas: Vec<UDelta2>) -> Self {
+        Self {
+            deltas,
+            corner_cutting: false,
+        }
+    }
+
+    // Value of the header option, None when it is not understood.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "4" => Some(Self::orthogonal()),
+            "8" => Some(Self::eight_directions(false)),
+            "8cut" => Some(Self::eight_directions(true)),
+            "knight" => Some(Self::knight()),
+            offsets => offsets
+                .split(',')
+                .map(|offset| {
+                    let (x, y) = offset.split_once(':')?;
+                    Some(UDelta2::new(x.parse().ok()?, y.parse().ok()?))
+                })
+                .collect::<Option<Vec<_>>>()
+                .filter(|deltas| !deltas.is_empty() && !deltas.contains(&UDelta2::new(0, 0)))
+                .map(Self::custom),
+        }
+    }
+
+    pub fn deltas(&self) -> &[UDelta2] {
+        &self.deltas
+    }
+
+    pub fn corner_cutting(&self) -> bool {
+        self.corner_cutting
+    }
+
+    pub fn is_orthogonal(&self) -> bool {
+        *self == Self::orthogonal()
+    }
+
+    // A diagonal move is checked against the two squares beside it, the other moves only need their destination.
+    pub fn allows(
+        &self,
+        from: UCoord2,
+        delta: UDelta2,
+        is_blocked: impl Fn(UCoord2) -> bool,
+    ) -> bool {
+        self.allows_beside(delta, |beside| {
+            from.checked_add_delta(beside).is_none_or(&is_blocked)
+        })
+    }
+
+    // Same, with the squares beside the move given by their delta from the square left.
+    pub fn allows_beside(
+        &self,
+        delta: UDelta2,
+        is_blocked_beside: impl Fn(UDelta2) -> bool,
+    ) -> bool {
+        if delta.x.abs() != 1 || delta.y.abs() != 1 {
+            return true;
+        }
+
+        let beside = [UDelta2::new(delta.x, 0), UDelta2::new(0, delta.y)];
+        let blocked = beside
+            .iter()
+            .filter(|&&beside| is_blocked_beside(beside))
+            .count();
+        blocked == 0 || (self.corner_cutting && blocked == 1)
+    }
+
+    // Lower bound of the number of moves : a move covers at most the longest Manhattan and the longest Chebyshev
+    // distance of the model. It is the Manhattan distance for the four moves, and the Chebyshev one, the octile
+    // distance when every move costs one turn, for the eight moves.
+    pub fn heuristic(&self, delta: UDelta2) -> i32 {
+        let manhattan = |delta: &UDelta2| delta.x.abs() + delta.y.abs();
+        let chebyshev = |delta: &UDelta2| delta.x.abs().max(delta.y.abs());
+        let max_manhattan = self.deltas.iter().map(manhattan).max().unwrap_or(1);
+        let max_chebyshev = self.deltas.iter().map(chebyshev).max().unwrap_or(1);
+
+        let div_ceil = |distance: i32, step: i32| (distance + step - 1) / step;
+        div_ceil(manhattan(&delta), max_manhattan).max(div_ceil(chebyshev(&delta), max_chebyshev))
+    }
+}
+
+// Action of a move : the CodinGame labels for the four orthogonal moves, UP_LEFT and the like for the diagonal ones,
+// and "MOVE dx dy" for any other one.
+pub fn label(delta: UDelta2) -> String {
+    let vertical = match delta.y {
+        -1 => Some("UP"),
+        1 => Some("DOWN"),
+        _ => None,
+    };
+    let horizontal = match delta.x {
+        -1 => Some("LEFT"),
+        1 => Some("RIGHT"),
+        _ => None,
+    };
+
+    match (delta.x, delta.y, vertical, horizontal) {
+        (0, _, Some(vertical), _) => vertical.to_owned(),
+        (_, 0, _, Some(horizontal)) => horizontal.to_owned(),
+        (_, _, Some(vertical), Some(horizontal)) => format!("{}_{}", vertical, horizontal),
+        _ => format!("MOVE {} {}", delta.x, delta.y),
+    }
+}
+
+pub fn from_label(label: &str) -> Option<UDelta2> {
+    if let Some(offset) = label.strip_prefix("MOVE ") {
+        let mut values = offset.split_whitespace().map(str::parse::<i32>);
+        return match (values.next(), values.next(), values.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => Some(UDelta2::new(x, y)),
+            _ => None,
+        };
+    }
+
+    let (vertical, horizontal) = match label.split_once('_') {
+        Some((vertical, horizontal)) => (vertical, horizontal),
+        None => return Direction::from_label(label).map(Direction::to_delta),
+    };
+    match (
+        Direction::from_label(vertical)?,
+        Direction::from_label(horizontal)?,
+    ) {
+        (
+            vertical @ (Direction::Up | Direction::Down),
+            horizontal @ (Direction::Left | Direction::Right),
+        ) => Some(UDelta2::new(horizontal.to_delta().x, vertical.to_delta().y)),
+        _ => None,
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    error, fmt,
+    io::{self, BufRead, Write},
+};
+
+use crate::solver::Solver;
+use crate::vect2_t::UCoord2;
+
+// Bigger labyrinths are refused before allocating the board, CodinGame ones are at most 200x100.
+pub const MAX_SIDE: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProtocolError {
+    // The input was closed, at the start of a turn it is the normal end of the game.
+    Eof,
+    // The init line "R C A" or the turn line "KR KC" cannot be parsed.
+    MalformedHeader(String),
+    // The input was closed in the middle of the rows of a turn.
+    WrongRowCount {
+        expected: usize,
+        actual: usize,
+    },
+    WrongRowWidth {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    KirkOutOfBounds {
+        coord: UCoord2,
+        width: usize,
+        height: usize,
+    },
+    // Reading the input or writing the action failed, invalid UTF-8 included.
+    Io(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => write!(f, "unexpected end of input"),
+            Self::MalformedHeader(line) => write!(f, "malformed header line {:?}", line),
+            Self::WrongRowCount { expected, actual } => {
+                write!(f, "expected {} rows, got {}", expected, actual)
+            }
+            Self::WrongRowWidth {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row {} is {} squares wide instead of {}",
+                row, actual, expected
+            ),
+            Self::KirkOutOfBounds {
+                coord,
+                width,
+                height,
+            } => write!(
+                f,
+                "Kirk position {} is out of the {}x{} labyrinth",
+                coord, width, height
+            ),
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl error::Error for ProtocolError {}
+
+impl From<io::Error> for ProtocolError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+fn parse_numbers(line: &str, count: usize) -> Result<Vec<usize>, ProtocolError> {
+    let malformed = || ProtocolError::MalformedHeader(line.trim_end().to_owned());
+    let numbers = line
+        .split_whitespace()
+        .map(|s| s.parse::<usize>().map_err(|_| malformed()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.len() == count {
+        Ok(numbers)
+    } else {
+        Err(malformed())
+    }
+}
+
+// What the init line tells the bot about the game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameSettings {
+    pub height: usize,
+    pub width: usize,
+    // rounds of the alarm countdown
+    pub rounds: usize,
+    // control rooms to visit before the alarm, more than 1 in the multiple rooms variant
+    pub cmd_rooms: usize,
+}
+
+impl GameSettings {
+    pub fn new(height: usize, width: usize, rounds: usize) -> Self {
+        Self {
+            height,
+            width,
+            rounds,
+            cmd_rooms: 1,
+        }
+    }
+}
+
+// The init line, the number of control rooms is only sent in the multiple rooms variant.
+impl fmt::Display for GameSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.height, self.width, self.rounds)?;
+        if self.cmd_rooms != 1 {
+            write!(f, " {}", self.cmd_rooms)?;
+        }
+        Ok(())
+    }
+}
+
+// "R C A [N]" : number of rows, number of columns, rounds of the alarm countdown,
+// and for the multiple rooms variant the number of control rooms to visit.
+pub fn parse_init_line(line: &str) -> Result<GameSettings, ProtocolError> {
+    let numbers = line
+        .split_whitespace()
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>();
+    let (height, width, rounds, cmd_rooms) = match numbers.as_deref() {
+        Ok(&[height, width, rounds]) => (height, width, rounds, 1),
+        Ok(&[height, width, rounds, cmd_rooms]) => (height, width, rounds, cmd_rooms),
+        _ => return Err(ProtocolError::MalformedHeader(line.trim_end().to_owned())),
+    };
+    if height > 0 && width > 0 && height <= MAX_SIDE && width <= MAX_SIDE && cmd_rooms > 0 {
+        Ok(GameSettings {
+            height,
+            width,
+            rounds,
+            cmd_rooms,
+        })
+    } else {
+        Err(ProtocolError::MalformedHeader(line.trim_end().to_owned()))
+    }
+}
+
+// "KR KC" : row and column where Rick is located.
+pub fn parse_turn_line(line: &str) -> Result<UCoord2, ProtocolError> {
+    let numbers = parse_numbers(line, 2)?;
+    Ok((numbers[1], numbers[0]).into())
+}
+
+// Reads one line, Eof when the input is closed.
+fn read_line<R: BufRead>(input: &mut R, line: &mut String) -> Result<(), ProtocolError> {
+    line.clear();
+    if input.read_line(line)? == 0 {
+        Err(ProtocolError::Eof)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn read_init<R: BufRead>(input: &mut R) -> Result<GameSettings, ProtocolError> {
+    let mut line = String::new();
+    read_line(input, &mut line)?;
+    parse_init_line(&line)
+}
+
+// Reads the turn line then the rows, checked against the labyrinth size given by the init line.
+pub fn read_turn<R: BufRead>(
+    input: &mut R,
+    rows: &mut [String],
+    width: usize,
+) -> Result<UCoord2, ProtocolError> {
+    let mut line = String::new();
+    read_line(input, &mut line)?;
+    let rick_coord = parse_turn_line(&line)?;
+
+    let height = rows.len();
+    for (y, row) in rows.iter_mut().enumerate() {
+        match read_line(input, row) {
+            Err(ProtocolError::Eof) => {
+                return Err(ProtocolError::WrongRowCount {
+                    expected: height,
+                    actual: y,
+                })
+            }
+            result => result?,
+        }
+        let actual = row.trim().len();
+        if actual != width {
+            return Err(ProtocolError::WrongRowWidth {
+                row: y,
+                expected: width,
+                actual,
+            });
+        }
+    }
+
+    if rick_coord.x >= width || rick_coord.y >= height {
+        return Err(ProtocolError::KirkOutOfBounds {
+            coord: rick_coord,
+            width,
+            height,
+        });
+    }
+
+    Ok(rick_coord)
+}
+
+// Play the game reading the CodinGame protocol from input and writing the actions to output.
+// The game ends normally when the solver has no move left or when the input is closed between two turns.
+pub fn play<R: BufRead, W: Write>(mut input: R, mut output: W) -> Result<(), ProtocolError> {
+    let settings = read_init(&mut input)?;
+
+    let mut solver = Solver::from_settings(&settings);
+
+    let mut input_board_buffer: Vec<_> = (0..settings.height)
+        .map(|_| String::with_capacity(settings.width + 1))
+        .collect();
+
+    loop {
+        let rick_coord = match read_turn(&mut input, &mut input_board_buffer, settings.width) {
+            Err(ProtocolError::Eof) => return Ok(()),
+            result => result?,
+        };
+
+        match solver.next_move(rick_coord, &input_board_buffer) {
+            Some(dir) => {
+                writeln!(output, "{}", dir)?;
+                output.flush()?;
+            }
+            None => return Ok(()),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::ops::Range;
 
 pub fn centered_range(center: usize, limit: usize) -> Range<usize> {
-    let start = if center < 2 { 0 } else { center - 2 };
+    let start = center.saturating_sub(2);
     let end = if center + 2 >= limit {
         limit
     } else {
@@ -10,3 +10,22 @@ pub fn centered_range(center: usize, limit: usize) -> Range<usize> {
     //    println!("{} {}", start, end);
     start..end
 }
+
+// Same window on a grid wrapping around its edges, every row or column once when the grid is smaller than it.
+pub fn wrapped_range(center: usize, limit: usize) -> Vec<usize> {
+    let mut range: Vec<usize> = (0..5)
+        .map(|offset| (center + limit * 2 + offset - 2) % limit)
+        .collect();
+    range.sort_unstable();
+    range.dedup();
+    range
+}
+
+// Rows or columns of the radar window.
+pub fn radar_range(center: usize, limit: usize, wraps: bool) -> Vec<usize> {
+    if wraps {
+        wrapped_range(center, limit)
+    } else {
+        centered_range(center, limit).collect()
+    }
+}
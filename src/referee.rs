@@ -0,0 +1,133 @@
+use std::{
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    process::{Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::game_sample::{GameSample, Outcome};
+
+// What the bot answered to a turn.
+enum Answer {
+    Action(String),
+    Closed,
+    Timeout,
+}
+
+// Time given to the bot to answer, CodinGame allows more for the first turn.
+#[derive(Debug, Clone, Copy)]
+pub struct TurnTimeouts {
+    pub first_turn: Duration,
+    pub turn: Duration,
+}
+
+impl Default for TurnTimeouts {
+    fn default() -> Self {
+        Self {
+            first_turn: Duration::from_millis(1000),
+            turn: Duration::from_millis(150),
+        }
+    }
+}
+
+// Referee a game of the labyrinth against a bot reached through streams (socket, pipes), with the CodinGame
+// line protocol: the init line, then for each turn Kirk's position and the rows, answered by one action line.
+// A bot closing its side of the stream gives up the game.
+pub fn referee<R: BufRead, W: Write>(
+    game_sample: &mut GameSample,
+    mut from_bot: R,
+    to_bot: W,
+) -> io::Result<Outcome> {
+    let result = exchange(game_sample, to_bot, |_| {
+        let mut action = String::new();
+        if from_bot.read_line(&mut action)? == 0 {
+            Ok(Answer::Closed)
+        } else {
+            Ok(Answer::Action(action))
+        }
+    });
+    finish(game_sample, result)
+}
+
+// Referee a game against an external executable, fed on its stdin and answering on its stdout,
+// as the CodinGame judge runs the submissions. The process is killed at the end of the game.
+pub fn referee_process(
+    game_sample: &mut GameSample,
+    command: &mut Command,
+    timeouts: TurnTimeouts,
+) -> io::Result<Outcome> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let to_bot = child.stdin.take().unwrap();
+    let from_bot = child.stdout.take().unwrap();
+
+    // The lines are read in another thread to wait for them with a timeout.
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(from_bot).lines() {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let result = exchange(game_sample, to_bot, |first_turn| {
+        let timeout = if first_turn {
+            timeouts.first_turn
+        } else {
+            timeouts.turn
+        };
+        match receiver.recv_timeout(timeout) {
+            Ok(line) => Ok(Answer::Action(line?)),
+            Err(RecvTimeoutError::Timeout) => Ok(Answer::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Ok(Answer::Closed),
+        }
+    });
+
+    // The bot may already be gone.
+    let _ = child.kill();
+    child.wait()?;
+
+    finish(game_sample, result)
+}
+
+fn finish(game_sample: &mut GameSample, result: io::Result<()>) -> io::Result<Outcome> {
+    match result {
+        Err(err) if is_disconnection(&err) => game_sample.stop(),
+        result => result?,
+    }
+    Ok(game_sample.outcome().unwrap())
+}
+
+fn is_disconnection(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::BrokenPipe | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+    )
+}
+
+// Sends the turns until the game is over, answer gets the action of the bot and is told whether it is the first turn.
+fn exchange<W, F>(game_sample: &mut GameSample, mut to_bot: W, mut answer: F) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(bool) -> io::Result<Answer>,
+{
+    to_bot.write_all(game_sample.init_data().as_bytes())?;
+
+    let mut first_turn = true;
+    while game_sample.outcome().is_none() {
+        to_bot.write_all(game_sample.round_data().as_bytes())?;
+        to_bot.flush()?;
+
+        match answer(first_turn)? {
+            Answer::Action(action) => game_sample.set_action(&action),
+            Answer::Closed => game_sample.stop(),
+            Answer::Timeout => game_sample.time_out(),
+        }
+        first_turn = false;
+    }
+    Ok(())
+}
@@ -0,0 +1,139 @@
+pub mod ansi;
+pub mod html;
+pub mod png;
+pub mod svg;
+
+use std::fmt;
+
+use crate::board::{Board, Content};
+use crate::grid::Grid;
+use crate::keys::KeyColor;
+use crate::replay::Replay;
+use crate::vect2_t::UCoord2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Color {
+    pub fn blend(self, other: Color, ratio: f64) -> Color {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * ratio).round() as u8;
+        Color(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+pub const WALL: Color = Color(0x3a, 0x3a, 0x46);
+pub const EMPTY: Color = Color(0xf2, 0xf0, 0xe6);
+pub const FOG: Color = Color(0x8a, 0x8f, 0x99);
+pub const START: Color = Color(0x4c, 0xaf, 0x50);
+pub const CMD_ROOM: Color = Color(0xff, 0xc1, 0x07);
+pub const KIRK: Color = Color(0xe5, 0x39, 0x35);
+pub const EXPLORATION_PATH: Color = Color(0x1e, 0x88, 0xe5);
+pub const RETURN_PATH: Color = Color(0xfb, 0x8c, 0x00);
+pub const PLAN: Color = Color(0x8e, 0x24, 0xaa);
+pub const PORTAL: Color = Color(0x26, 0xc6, 0xda);
+pub const HAZARD: Color = Color(0xff, 0x57, 0x22);
+pub const GUARD: Color = Color(0x6d, 0x4c, 0x41);
+
+// Keys have the colour of their doors, doors are darker.
+pub fn key_color(color: KeyColor) -> Color {
+    match color {
+        KeyColor::Red => Color(0xef, 0x53, 0x50),
+        KeyColor::Green => Color(0x66, 0xbb, 0x6a),
+        KeyColor::Blue => Color(0x42, 0xa5, 0xf5),
+        KeyColor::Yellow => Color(0xff, 0xee, 0x58),
+    }
+}
+
+pub fn door_color(color: KeyColor) -> Color {
+    key_color(color).blend(WALL, 0.4)
+}
+
+const HEAT_LOW: Color = Color(0xff, 0xf1, 0x76);
+const HEAT_HIGH: Color = Color(0xc6, 0x28, 0x28);
+
+const FIRST_DISCOVERY: Color = Color(0xe3, 0xf2, 0xfd);
+const LAST_DISCOVERY: Color = Color(0x90, 0xa4, 0xd4);
+
+// Color of a square in the labyrinth file format.
+pub fn cell_color(cell: u8) -> Color {
+    match cell {
+        b'#' => WALL,
+        b'T' => START,
+        b'C' => CMD_ROOM,
+        b'?' => FOG,
+        b'0'..=b'9' => PORTAL,
+        b'~' => HAZARD,
+        b'<' | b'>' | b'^' | b'v' => GUARD,
+        cell => match (KeyColor::from_key_byte(cell), KeyColor::from_door_byte(cell)) {
+            (Some(color), _) => key_color(color),
+            (_, Some(color)) => door_color(color),
+            _ => EMPTY,
+        },
+    }
+}
+
+// Empty squares get darker the later they have been discovered.
+pub fn discovery_color(turn: usize, last_turn: usize) -> Color {
+    FIRST_DISCOVERY.blend(LAST_DISCOVERY, turn as f64 / last_turn.max(1) as f64)
+}
+
+// Squares visited many times get redder.
+pub fn heat_color(visits: usize, max_visits: usize) -> Color {
+    if max_visits <= 1 {
+        HEAT_LOW
+    } else {
+        HEAT_LOW.blend(HEAT_HIGH, (visits - 1) as f64 / (max_visits - 1) as f64)
+    }
+}
+
+// Kirk's knowledge of the labyrinth, in the labyrinth file format.
+pub fn board_cells(board: &Board) -> Grid<u8> {
+    let mut cells = Grid::new(board.width(), board.height(), b'?');
+    for (coord, cell) in cells.iter_mut() {
+        *cell = if Some(coord) == board.cmd_room_coord() {
+            b'C'
+        } else if Some(coord) == board.rick_start_coord() {
+            b'T'
+        } else {
+            match board.get_content(&coord) {
+                Content::Unknown => b'?',
+                Content::Wall => b'#',
+                Content::Empty => b'.',
+                Content::Key(color) => color.key_byte(),
+                Content::Door(color) => color.door_byte(),
+                Content::Portal(label) => b'0' + label,
+                Content::Hazard => b'~',
+            }
+        };
+    }
+    cells
+}
+
+// The true map, with the starting point marked even if the sample file doesn't.
+pub fn map_cells(replay: &Replay) -> Grid<u8> {
+    let mut cells = replay.map().clone();
+    cells[replay.start()] = b'T';
+    cells
+}
+
+// Split the trajectory in the exploration part and the return part (both contain the control room).
+pub fn split_trajectory(replay: &Replay) -> (Vec<UCoord2>, Vec<UCoord2>) {
+    let trajectory = replay.trajectory();
+    match replay.alarm_turn() {
+        Some(alarm_turn) => (
+            trajectory[..=alarm_turn].to_vec(),
+            trajectory[alarm_turn..].to_vec(),
+        ),
+        None => (trajectory, Vec::new()),
+    }
+}
@@ -0,0 +1,38 @@
+use crate::render::{self, Color};
+use crate::replay::Replay;
+use crate::stats::GameStats;
+
+fn push_colored(out: &mut String, background: Color, text: &str) {
+    out.push_str(&format!(
+        "\x1b[48;2;{};{};{}m\x1b[38;2;0;0;0m{}\x1b[0m",
+        background.0, background.1, background.2, text
+    ));
+}
+
+// Visits per square for a terminal with true colors, each square is two characters wide.
+pub fn heatmap(replay: &Replay, stats: &GameStats) -> String {
+    let cells = render::map_cells(replay);
+    let mut out = String::new();
+
+    for (row, visits_row) in cells.rows().zip(stats.visits.rows()) {
+        for (&cell, &visits) in row.iter().zip(visits_row) {
+            if visits == 0 {
+                push_colored(&mut out, render::cell_color(cell), "  ");
+            } else {
+                let text = if visits < 100 {
+                    format!("{:>2}", visits)
+                } else {
+                    "++".to_owned()
+                };
+                push_colored(
+                    &mut out,
+                    render::heat_color(visits, stats.max_visits),
+                    &text,
+                );
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
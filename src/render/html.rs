@@ -0,0 +1,287 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::movement;
+use crate::render;
+use crate::replay::Replay;
+use crate::vect2_t::UCoord2;
+
+fn coords_json(coords: &[UCoord2]) -> String {
+    let items = coords
+        .iter()
+        .map(|coord| format!("[{},{}]", coord.x, coord.y))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{}]", items)
+}
+
+// JSON string of the text, quotes, backslashes, control characters and '<' escaped.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            // the page reads the data inside a script element, that "</script>" would close
+            '<' => json.push_str("\\u003c"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// Colour of every square of the map and of the fog, keyed by its character.
+fn cell_colors_json(replay: &Replay) -> String {
+    let cells: BTreeSet<u8> = render::map_cells(replay)
+        .iter()
+        .map(|(_, &cell)| cell)
+        .chain(Some(b'?'))
+        .collect();
+    let items = cells
+        .into_iter()
+        .map(|cell| {
+            format!(
+                "{}:\"{}\"",
+                json_string(&char::from(cell).to_string()),
+                render::cell_color(cell)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", items)
+}
+
+// Replay data as a JS object literal.
+fn replay_json(replay: &Replay) -> String {
+    let mut json = String::new();
+
+    let rows = render::map_cells(replay)
+        .rows()
+        .map(|row| json_string(&String::from_utf8_lossy(row)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    write!(
+        json,
+        "{{\"width\":{},\"height\":{},\"rounds\":{},\"alarmTurn\":{},\"map\":[{}],\"turns\":[",
+        replay.width(),
+        replay.height(),
+        replay.rounds(),
+        replay
+            .alarm_turn()
+            .map_or("null".to_owned(), |turn| turn.to_string()),
+        rows
+    )
+    .unwrap();
+
+    for (i, turn) in replay.turns().iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write!(
+            json,
+            "{{\"kirk\":[{},{}],\"revealed\":{},\"action\":{},\"plan\":{}}}",
+            turn.kirk_coord.x,
+            turn.kirk_coord.y,
+            coords_json(&turn.revealed),
+            turn.action
+                .map_or("null".to_owned(), |delta| format!("\"{}\"", movement::label(delta))),
+            coords_json(&turn.plan)
+        )
+        .unwrap();
+    }
+
+    write!(
+        json,
+        "],\"trajectory\":{}}}",
+        coords_json(&replay.trajectory())
+    )
+    .unwrap();
+    json
+}
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; background: #fafafa; color: #222; margin: 1em; }
+.panels { display: flex; flex-wrap: wrap; gap: 1.5em; }
+.panel h2 { font-size: 1em; margin: 0.3em 0; }
+canvas { border: 1px solid #ccc; image-rendering: pixelated; }
+.controls { margin: 1em 0; display: flex; align-items: center; gap: 1em; }
+#turn { width: 40em; }
+#info { font-family: monospace; }
+"#;
+
+const SCRIPT: &str = r#"
+const CELL = Math.max(4, Math.min(24, Math.floor(720 / REPLAY.width)));
+const slider = document.getElementById('turn');
+const info = document.getElementById('info');
+const playButton = document.getElementById('play');
+const showPlan = document.getElementById('show-plan');
+const showTrajectory = document.getElementById('show-trajectory');
+const truthCanvas = document.getElementById('truth');
+const knowledgeCanvas = document.getElementById('knowledge');
+const frameCount = REPLAY.trajectory.length;
+
+for (const canvas of [truthCanvas, knowledgeCanvas]) {
+    canvas.width = REPLAY.width * CELL;
+    canvas.height = REPLAY.height * CELL;
+}
+slider.max = frameCount - 1;
+
+// discovery[y][x] = turn at which the radar revealed the square
+const discovery = REPLAY.map.map(row => Array(row.length).fill(Infinity));
+REPLAY.turns.forEach((turn, t) => turn.revealed.forEach(([x, y]) => discovery[y][x] = t));
+
+function cellColor(c) {
+    return CELL_COLORS[c];
+}
+
+function fillCell(ctx, x, y, color, margin) {
+    ctx.fillStyle = color;
+    ctx.fillRect(x * CELL + margin, y * CELL + margin, CELL - 2 * margin, CELL - 2 * margin);
+}
+
+function drawPath(ctx, coords, color, width) {
+    if (coords.length < 2) return;
+    ctx.strokeStyle = color;
+    ctx.lineWidth = width;
+    ctx.lineJoin = 'round';
+    ctx.beginPath();
+    coords.forEach(([x, y], i) => {
+        const px = x * CELL + CELL / 2, py = y * CELL + CELL / 2;
+        if (i === 0) ctx.moveTo(px, py); else ctx.lineTo(px, py);
+    });
+    ctx.stroke();
+}
+
+function drawTrajectory(ctx, t) {
+    const walked = REPLAY.trajectory.slice(0, t + 1);
+    if (REPLAY.alarmTurn === null || t <= REPLAY.alarmTurn) {
+        drawPath(ctx, walked, COLORS.exploration, CELL / 4);
+    } else {
+        drawPath(ctx, walked.slice(0, REPLAY.alarmTurn + 1), COLORS.exploration, CELL / 4);
+        drawPath(ctx, walked.slice(REPLAY.alarmTurn), COLORS.return, CELL / 4);
+    }
+}
+
+function drawKirk(ctx, [x, y]) {
+    ctx.fillStyle = COLORS.kirk;
+    ctx.beginPath();
+    ctx.arc(x * CELL + CELL / 2, y * CELL + CELL / 2, CELL / 3, 0, 2 * Math.PI);
+    ctx.fill();
+}
+
+function draw(t) {
+    const truth = truthCanvas.getContext('2d');
+    const knowledge = knowledgeCanvas.getContext('2d');
+    REPLAY.map.forEach((row, y) => [...row].forEach((c, x) => {
+        fillCell(truth, x, y, cellColor(c), 0);
+        fillCell(knowledge, x, y, discovery[y][x] <= t ? cellColor(c) : CELL_COLORS['?'], 0);
+    }));
+    for (const ctx of [truth, knowledge]) {
+        if (showTrajectory.checked) drawTrajectory(ctx, t);
+        drawKirk(ctx, REPLAY.trajectory[t]);
+    }
+    const turn = REPLAY.turns[t];
+    if (turn && showPlan.checked) {
+        drawPath(knowledge, [turn.kirk].concat(turn.plan), COLORS.plan, CELL / 6);
+        turn.plan.forEach(([x, y]) => fillCell(knowledge, x, y, COLORS.plan, CELL * 3 / 8));
+    }
+    const phase = REPLAY.alarmTurn !== null && t >= REPLAY.alarmTurn ? 'return' : 'exploration';
+    const [kx, ky] = REPLAY.trajectory[t];
+    info.textContent = `turn ${t} / ${frameCount - 1} | Kirk (${kx},${ky}) | ${phase}`
+        + (turn ? ` | revealed ${turn.revealed.length} | action ${turn.action || '-'} | plan ${turn.plan.length} squares` : ' | end of game');
+}
+
+let timer = null;
+playButton.onclick = () => {
+    if (timer) {
+        clearInterval(timer);
+        timer = null;
+        playButton.textContent = 'Play';
+        return;
+    }
+    if (+slider.value >= frameCount - 1) slider.value = 0;
+    playButton.textContent = 'Pause';
+    timer = setInterval(() => {
+        if (+slider.value >= frameCount - 1) return playButton.onclick();
+        slider.value = +slider.value + 1;
+        draw(+slider.value);
+    }, 150);
+};
+slider.oninput = () => draw(+slider.value);
+showPlan.onchange = showTrajectory.onchange = () => draw(+slider.value);
+document.addEventListener('keydown', e => {
+    if (e.key === 'ArrowRight') slider.value = Math.min(frameCount - 1, +slider.value + 1);
+    else if (e.key === 'ArrowLeft') slider.value = Math.max(0, +slider.value - 1);
+    else return;
+    draw(+slider.value);
+});
+draw(0);
+"#;
+
+// Text escaped for the content and the attribute values of the page.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Self contained page (no network access) to browse a recorded game turn by turn.
+pub fn replay_html(replay: &Replay, title: &str) -> String {
+    let mut html = String::new();
+
+    writeln!(
+        html,
+        r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>{style}</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div class="controls">
+<button id="play">Play</button>
+<input id="turn" type="range" min="0" max="0" value="0">
+<label><input id="show-trajectory" type="checkbox" checked> trajectory</label>
+<label><input id="show-plan" type="checkbox" checked> planner</label>
+</div>
+<div id="info"></div>
+<div class="panels">
+<div class="panel"><h2>True map</h2><canvas id="truth"></canvas></div>
+<div class="panel"><h2>Kirk's knowledge</h2><canvas id="knowledge"></canvas></div>
+</div>
+<script>
+const REPLAY = {replay};
+const CELL_COLORS = {cell_colors};
+const COLORS = {{kirk: "{kirk}", exploration: "{exploration}", return: "{return_path}", plan: "{plan}"}};
+{script}
+</script>
+</body>
+</html>"##,
+        title = escape(title),
+        style = STYLE,
+        replay = replay_json(replay),
+        cell_colors = cell_colors_json(replay),
+        kirk = render::KIRK,
+        exploration = render::EXPLORATION_PATH,
+        return_path = render::RETURN_PATH,
+        plan = render::PLAN,
+        script = SCRIPT
+    )
+    .unwrap();
+
+    html
+}
@@ -0,0 +1,160 @@
+use std::{fs, io, path::Path};
+
+use crate::render::{self, Color};
+use crate::replay::Replay;
+use crate::vect2_t::UCoord2;
+
+const CELL_SIZE: usize = 8;
+
+// RGB raster, enough to draw squares of the labyrinth.
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                let idx = (py * self.width + px) * 3;
+                self.pixels[idx..idx + 3].copy_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+    }
+
+    // Fill a labyrinth square, shrunk by `margin` pixels on each side.
+    pub fn fill_cell(&mut self, coord: UCoord2, margin: usize, color: Color) {
+        self.fill_rect(
+            coord.x * CELL_SIZE + margin,
+            coord.y * CELL_SIZE + margin,
+            CELL_SIZE - 2 * margin,
+            CELL_SIZE - 2 * margin,
+            color,
+        );
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Minimal PNG encoder (8 bits RGB, uncompressed deflate blocks) : no dependency needed.
+pub fn encode_png(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    for y in 0..height {
+        raw.push(0); // no filter
+        raw.extend_from_slice(&rgb[y * width * 3..(y + 1) * width * 3]);
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_last = blocks.peek().is_none();
+        zlib.push(is_last as u8);
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    push_chunk(&mut png, b"IHDR", &ihdr);
+    push_chunk(&mut png, b"IDAT", &zlib);
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// Picture of the game at the given turn : what Kirk has seen so far, and where he went.
+pub fn frame(replay: &Replay, turn: usize) -> Canvas {
+    let mut canvas = Canvas::new(replay.width() * CELL_SIZE, replay.height() * CELL_SIZE);
+
+    let cells = render::map_cells(replay);
+    let discovery_turns = replay.discovery_turns();
+
+    for (coord, (&cell, &discovery_turn)) in cells.zip(&discovery_turns).iter() {
+        let color = match discovery_turn {
+            Some(discovery_turn) if discovery_turn <= turn => render::cell_color(cell),
+            _ => render::FOG,
+        };
+        canvas.fill_cell(coord, 0, color);
+    }
+
+    let trajectory = replay.trajectory();
+    let alarm_turn = replay.alarm_turn().unwrap_or(trajectory.len());
+    let current = turn.min(trajectory.len().saturating_sub(1));
+
+    for (i, &coord) in trajectory.iter().enumerate().take(current) {
+        let color = if i < alarm_turn {
+            render::EXPLORATION_PATH
+        } else {
+            render::RETURN_PATH
+        };
+        canvas.fill_cell(coord, CELL_SIZE * 3 / 8, color);
+    }
+    if let Some(&coord) = trajectory.get(current) {
+        canvas.fill_cell(coord, 1, render::KIRK);
+    }
+
+    canvas
+}
+
+// Write one PNG per turn in `dir` (frame_0000.png, frame_0001.png, ...), returns the frame count.
+pub fn write_frames(replay: &Replay, dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let frame_count = replay.trajectory().len();
+    for turn in 0..frame_count {
+        let path = dir.join(format!("frame_{:04}.png", turn));
+        fs::write(path, frame(replay, turn).to_png())?;
+    }
+    Ok(frame_count)
+}
@@ -0,0 +1,374 @@
+use std::fmt::Write;
+
+use crate::board::Board;
+use crate::grid::Grid;
+use crate::render::{self, Color};
+use crate::replay::Replay;
+use crate::stats::GameStats;
+use crate::topology::Topology;
+use crate::vect2_t::UCoord2;
+
+const CELL_SIZE: usize = 16;
+
+fn center(coord: UCoord2) -> (usize, usize) {
+    (
+        coord.x * CELL_SIZE + CELL_SIZE / 2,
+        coord.y * CELL_SIZE + CELL_SIZE / 2,
+    )
+}
+
+fn push_header(svg: &mut String, width: usize, height: usize) {
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" shape-rendering="crispEdges">"#,
+        w = width * CELL_SIZE,
+        h = height * CELL_SIZE
+    )
+    .unwrap();
+}
+
+fn push_rect(svg: &mut String, coord: UCoord2, color: Color, extra: &str) {
+    write!(
+        svg,
+        r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"{}"#,
+        coord.x * CELL_SIZE,
+        coord.y * CELL_SIZE,
+        color,
+        extra,
+        s = CELL_SIZE
+    )
+    .unwrap();
+}
+
+fn push_cells(svg: &mut String, cells: &Grid<u8>) {
+    for (coord, &cell) in cells.iter() {
+        push_rect(svg, coord, render::cell_color(cell), "/>\n");
+    }
+}
+
+// Whether the step is drawn as a line : a move of the topology between squares side by side in the picture, not a
+// move across a wrapped edge nor a jump through a portal.
+fn is_drawn_step(topology: &dyn Topology, from: UCoord2, to: UCoord2) -> bool {
+    from == to
+        || topology
+            .deltas(from)
+            .iter()
+            .any(|&delta| from.checked_add_delta(delta) == Some(to))
+}
+
+// One polyline per run of drawn steps of the path.
+fn push_polyline(
+    svg: &mut String,
+    topology: &dyn Topology,
+    coords: &[UCoord2],
+    color: Color,
+    point: impl Fn(UCoord2) -> String,
+) {
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    for i in 1..=coords.len() {
+        if i == coords.len() || !is_drawn_step(topology, coords[i - 1], coords[i]) {
+            runs.push(&coords[run_start..i]);
+            run_start = i;
+        }
+    }
+
+    for run in runs.into_iter().filter(|run| run.len() >= 2) {
+        let points = run
+            .iter()
+            .map(|&coord| point(coord))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="3" stroke-linejoin="round" stroke-opacity="0.8"/>"#,
+            points, color
+        )
+        .unwrap();
+    }
+}
+
+fn square_point(coord: UCoord2) -> String {
+    let (cx, cy) = center(coord);
+    format!("{},{}", cx, cy)
+}
+
+fn push_kirk(svg: &mut String, coord: UCoord2) {
+    let (cx, cy) = center(coord);
+    writeln!(
+        svg,
+        r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+        cx,
+        cy,
+        CELL_SIZE / 3,
+        render::KIRK
+    )
+    .unwrap();
+}
+
+// Kirk's knowledge of the labyrinth, as the solver sees it.
+pub fn board_svg(board: &Board) -> String {
+    let mut svg = String::new();
+    push_header(&mut svg, board.width(), board.height());
+    push_cells(&mut svg, &render::board_cells(board));
+    if let Some(coord) = board.rick_current_coord() {
+        push_kirk(&mut svg, coord);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// The true labyrinth of a game sample.
+pub fn map_svg(replay: &Replay) -> String {
+    let mut svg = String::new();
+    push_header(&mut svg, replay.width(), replay.height());
+    push_cells(&mut svg, &render::map_cells(replay));
+    push_kirk(&mut svg, replay.start());
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Centre of a pointy top hexagon of the odd-r offset grid, odd rows shifted half a cell to the right.
+fn hex_center(coord: UCoord2) -> (f64, f64) {
+    let width = CELL_SIZE as f64;
+    let shift = if coord.y % 2 == 1 { width / 2. } else { 0. };
+    (
+        coord.x as f64 * width + width / 2. + shift,
+        coord.y as f64 * hex_row_height() + hex_radius(),
+    )
+}
+
+fn hex_radius() -> f64 {
+    CELL_SIZE as f64 / 3f64.sqrt()
+}
+
+fn hex_row_height() -> f64 {
+    hex_radius() * 1.5
+}
+
+// The true labyrinth of a hex game sample with the trajectory of Kirk.
+pub fn hex_svg(replay: &Replay) -> String {
+    let width = (replay.width() as f64 + 0.5) * CELL_SIZE as f64;
+    let height = replay.height() as f64 * hex_row_height() + hex_radius() / 2.;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{h:.0}" viewBox="0 0 {w:.1} {h:.1}">"#,
+        w = width,
+        h = height
+    )
+    .unwrap();
+
+    for (coord, &cell) in render::map_cells(replay).iter() {
+        let (cx, cy) = hex_center(coord);
+        let points = (0..6)
+            .map(|corner| {
+                let angle = std::f64::consts::PI / 3. * corner as f64 + std::f64::consts::PI / 6.;
+                format!(
+                    "{:.1},{:.1}",
+                    cx + hex_radius() * angle.cos(),
+                    cy + hex_radius() * angle.sin()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{}"/>"#,
+            points,
+            render::cell_color(cell)
+        )
+        .unwrap();
+    }
+
+    let (exploration_path, return_path) = render::split_trajectory(replay);
+    for (path, color) in &[
+        (exploration_path, render::EXPLORATION_PATH),
+        (return_path, render::RETURN_PATH),
+    ] {
+        push_polyline(
+            &mut svg,
+            replay.topology().as_ref(),
+            path,
+            *color,
+            |coord| {
+                let (cx, cy) = hex_center(coord);
+                format!("{:.1},{:.1}", cx, cy)
+            },
+        );
+    }
+
+    let (cx, cy) = hex_center(replay.start());
+    writeln!(
+        svg,
+        r#"<circle cx="{:.1}" cy="{:.1}" r="{}" fill="{}"/>"#,
+        cx,
+        cy,
+        CELL_SIZE / 3,
+        render::KIRK
+    )
+    .unwrap();
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// The whole game in one picture : squares are shaded by discovery order (hover shows the turn),
+// squares never seen stay in the fog, and the trajectory is split into exploration and return.
+pub fn trace_svg(replay: &Replay) -> String {
+    let mut svg = String::new();
+    push_header(&mut svg, replay.width(), replay.height());
+
+    let cells = render::map_cells(replay);
+    let discovery_turns = replay.discovery_turns();
+    let last_turn = replay.turns().len().saturating_sub(1);
+
+    for (coord, (&cell, &discovery_turn)) in cells.zip(&discovery_turns).iter() {
+        match discovery_turn {
+            Some(turn) => {
+                let color = if cell == b'.' {
+                    render::discovery_color(turn, last_turn)
+                } else {
+                    render::cell_color(cell)
+                };
+                push_rect(&mut svg, coord, color, "");
+                writeln!(svg, "><title>discovered at turn {}</title></rect>", turn).unwrap();
+            }
+            None => {
+                push_rect(&mut svg, coord, render::cell_color(cell), "/>\n");
+                push_rect(&mut svg, coord, render::FOG, r#" fill-opacity="0.75"/>"#);
+                svg.push('\n');
+            }
+        }
+    }
+
+    let (exploration_path, return_path) = render::split_trajectory(replay);
+    let topology = replay.topology().as_ref();
+    push_polyline(
+        &mut svg,
+        topology,
+        &exploration_path,
+        render::EXPLORATION_PATH,
+        square_point,
+    );
+    push_polyline(
+        &mut svg,
+        topology,
+        &return_path,
+        render::RETURN_PATH,
+        square_point,
+    );
+
+    if let Some(&coord) = replay.trajectory().last() {
+        push_kirk(&mut svg, coord);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// The game as an SMIL animation : fog lifts as the radar reveals squares and Kirk walks his trajectory.
+pub fn animated_trace_svg(replay: &Replay, turn_duration: f64) -> String {
+    let mut svg = String::new();
+    push_header(&mut svg, replay.width(), replay.height());
+
+    let cells = render::map_cells(replay);
+    push_cells(&mut svg, &cells);
+
+    for (coord, &discovery_turn) in replay.discovery_turns().iter() {
+        match discovery_turn {
+            Some(0) => (),
+            Some(turn) => {
+                push_rect(&mut svg, coord, render::FOG, "");
+                writeln!(
+                    svg,
+                    r#"><set attributeName="visibility" to="hidden" begin="{:.3}s" fill="freeze"/></rect>"#,
+                    turn as f64 * turn_duration
+                )
+                .unwrap();
+            }
+            None => {
+                push_rect(&mut svg, coord, render::FOG, "/>\n");
+            }
+        }
+    }
+
+    let trajectory = replay.trajectory();
+    let alarm_turn = replay.alarm_turn().unwrap_or(trajectory.len());
+    for (i, step) in trajectory.windows(2).enumerate() {
+        if !is_drawn_step(replay.topology().as_ref(), step[0], step[1]) {
+            continue;
+        }
+        let (x1, y1) = center(step[0]);
+        let (x2, y2) = center(step[1]);
+        let color = if i < alarm_turn {
+            render::EXPLORATION_PATH
+        } else {
+            render::RETURN_PATH
+        };
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="3" stroke-opacity="0.8" visibility="hidden"><set attributeName="visibility" to="visible" begin="{:.3}s" fill="freeze"/></line>"#,
+            x1,
+            y1,
+            x2,
+            y2,
+            color,
+            (i + 1) as f64 * turn_duration
+        )
+        .unwrap();
+    }
+
+    if let Some(&first) = trajectory.first() {
+        let (cx, cy) = center(first);
+        let xs = trajectory
+            .iter()
+            .map(|&coord| center(coord).0.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        let ys = trajectory
+            .iter()
+            .map(|&coord| center(coord).1.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        let duration = trajectory.len() as f64 * turn_duration;
+        writeln!(
+            svg,
+            r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{color}"><animate attributeName="cx" values="{xs}" dur="{d:.3}s" calcMode="discrete" fill="freeze"/><animate attributeName="cy" values="{ys}" dur="{d:.3}s" calcMode="discrete" fill="freeze"/></circle>"#,
+            cx = cx,
+            cy = cy,
+            r = CELL_SIZE / 3,
+            color = render::KIRK,
+            xs = xs,
+            ys = ys,
+            d = duration
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Visits per square, hover shows the count.
+pub fn heatmap_svg(replay: &Replay, stats: &GameStats) -> String {
+    let mut svg = String::new();
+    push_header(&mut svg, replay.width(), replay.height());
+
+    for (coord, (&cell, &visits)) in render::map_cells(replay).zip(&stats.visits).iter() {
+        match visits {
+            0 => push_rect(&mut svg, coord, render::cell_color(cell), "/>\n"),
+            visits => {
+                push_rect(
+                    &mut svg,
+                    coord,
+                    render::heat_color(visits, stats.max_visits),
+                    "",
+                );
+                writeln!(svg, "><title>{} visits</title></rect>", visits).unwrap();
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
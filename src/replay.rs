@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use crate::grid::Grid;
+use crate::movement::Movement;
+use crate::vect2_t::{UCoord2, UDelta2};
+use crate::stats;
+use crate::topology::Topology;
+
+// One round of a recorded game : where Kirk stands when he receives the round data,
+// the squares the radar revealed for the first time, the move he answered,
+// the path the solver was planning to follow at that time and whether it was backtracking.
+#[derive(Debug, Clone)]
+pub struct Turn {
+    pub kirk_coord: UCoord2,
+    pub revealed: Vec<UCoord2>,
+    pub action: Option<UDelta2>,
+    pub plan: Vec<UCoord2>,
+    pub backtracking: bool,
+}
+
+// Replay is the record of a whole game played against a GameSample.
+// It keeps the true map so a game can be rendered without the sample file.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    map: Grid<u8>,
+    start: UCoord2,
+    rounds: usize,
+    cmd_rooms_to_visit: usize,
+    topology: Arc<dyn Topology>,
+    turns: Vec<Turn>,
+}
+
+impl Replay {
+    pub fn new(map: Grid<u8>, start: UCoord2, rounds: usize) -> Self {
+        Self {
+            map,
+            start,
+            rounds,
+            cmd_rooms_to_visit: 1,
+            topology: Arc::new(Movement::orthogonal()),
+            turns: Vec::new(),
+        }
+    }
+
+    // More than 1 in the multiple rooms variant, the last one triggers the alarm.
+    pub fn set_cmd_rooms_to_visit(&mut self, cmd_rooms_to_visit: usize) {
+        self.cmd_rooms_to_visit = cmd_rooms_to_visit;
+    }
+
+    pub fn cmd_rooms_to_visit(&self) -> usize {
+        self.cmd_rooms_to_visit
+    }
+
+    // The grid and the moves of the game, the four orthogonal moves by default.
+    pub fn set_topology(&mut self, topology: Arc<dyn Topology>) {
+        self.topology = topology;
+    }
+
+    pub fn topology(&self) -> &Arc<dyn Topology> {
+        &self.topology
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.height()
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    pub fn map(&self) -> &Grid<u8> {
+        &self.map
+    }
+
+    pub fn cell(&self, coord: UCoord2) -> u8 {
+        self.map[coord]
+    }
+
+    pub fn start(&self) -> UCoord2 {
+        self.start
+    }
+
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    pub fn push_turn(&mut self, kirk_coord: UCoord2, revealed: Vec<UCoord2>) {
+        self.turns.push(Turn {
+            kirk_coord,
+            revealed,
+            action: None,
+            plan: Vec::new(),
+            backtracking: false,
+        });
+    }
+
+    pub fn set_last_action(&mut self, action: UDelta2) {
+        if let Some(turn) = self.turns.last_mut() {
+            turn.action = Some(action);
+        }
+    }
+
+    pub fn set_last_decision(&mut self, plan: Vec<UCoord2>, backtracking: bool) {
+        if let Some(turn) = self.turns.last_mut() {
+            turn.plan = plan;
+            turn.backtracking = backtracking;
+        }
+    }
+
+    // Every control room of the map, row by row.
+    pub fn cmd_room_coords(&self) -> Vec<UCoord2> {
+        self.map
+            .iter()
+            .filter(|&(_, &c)| c == b'C')
+            .map(|(coord, _)| coord)
+            .collect()
+    }
+
+    // Every square Kirk stood on, including the one reached with the last action.
+    pub fn trajectory(&self) -> Vec<UCoord2> {
+        let mut trajectory: Vec<_> = self.turns.iter().map(|turn| turn.kirk_coord).collect();
+        if let Some(Turn {
+            kirk_coord,
+            action: Some(delta),
+            ..
+        }) = self.turns.last()
+        {
+            // the last move may wrap around the map
+            if let Some(dest) = self
+                .topology
+                .neighbour(*kirk_coord, *delta, self.width(), self.height())
+            {
+                trajectory.push(stats::portal_exit(&self.map, dest).unwrap_or(dest));
+            }
+        }
+        trajectory
+    }
+
+    // Index in the trajectory of the first time Kirk reaches the control room,
+    // the last one to visit in the multiple rooms variant.
+    pub fn alarm_turn(&self) -> Option<usize> {
+        let mut visited_cmd_rooms = Vec::new();
+        self.trajectory().iter().position(|&coord| {
+            if self.cell(coord) == b'C' && !visited_cmd_rooms.contains(&coord) {
+                visited_cmd_rooms.push(coord);
+            }
+            visited_cmd_rooms.len() == self.cmd_rooms_to_visit
+        })
+    }
+
+    // For each square, the turn at which the radar revealed it, if ever.
+    pub fn discovery_turns(&self) -> Grid<Option<usize>> {
+        let mut discovery_turns = Grid::new(self.width(), self.height(), None);
+        for (turn_idx, turn) in self.turns.iter().enumerate() {
+            for &coord in &turn.revealed {
+                discovery_turns[coord] = Some(turn_idx);
+            }
+        }
+        discovery_turns
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    fmt::Display,
+    io::{self, BufReader, ErrorKind, Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+#[cfg(unix)]
+use std::{fs, os::unix::net::UnixListener, path::Path};
+
+use crate::game_sample::{GameSample, FUEL};
+use crate::referee::referee;
+
+// Serve the labyrinth to every bot connecting to the TCP address, one game per connection.
+pub fn serve_tcp(address: &str, labyrinth_filename: &str) -> io::Result<()> {
+    check_labyrinth(labyrinth_filename)?;
+    let listener = TcpListener::bind(address)?;
+    eprintln!(
+        "Serving {} on {}",
+        labyrinth_filename,
+        listener.local_addr()?
+    );
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        // one short line per turn, Nagle's algorithm would delay every answer
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr()?;
+        spawn_game(peer, stream, labyrinth_filename);
+    }
+    Ok(())
+}
+
+// Same as serve_tcp on a Unix socket, the socket file is replaced if it already exists.
+#[cfg(unix)]
+pub fn serve_unix(path: &Path, labyrinth_filename: &str) -> io::Result<()> {
+    check_labyrinth(labyrinth_filename)?;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    eprintln!("Serving {} on {}", labyrinth_filename, path.display());
+
+    for (game, stream) in listener.incoming().enumerate() {
+        spawn_game(format!("game {}", game), stream?, labyrinth_filename);
+    }
+    Ok(())
+}
+
+// A labyrinth file that cannot be played is refused before listening.
+fn check_labyrinth(labyrinth_filename: &str) -> io::Result<()> {
+    GameSample::new(labyrinth_filename)
+        .map(|_| ())
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+}
+
+fn spawn_game<P, S>(peer: P, stream: S, labyrinth_filename: &str)
+where
+    P: Display + Send + 'static,
+    S: Send + 'static,
+    for<'a> &'a S: Read + Write,
+{
+    let labyrinth_filename = labyrinth_filename.to_owned();
+    thread::spawn(move || {
+        let mut game_sample = match GameSample::new(&labyrinth_filename) {
+            Ok(game_sample) => game_sample,
+            Err(err) => return eprintln!("{}: cannot load {}: {}", peer, labyrinth_filename, err),
+        };
+        match referee(&mut game_sample, BufReader::new(&stream), &stream) {
+            Ok(outcome) => eprintln!(
+                "{}: {:?} after {} moves",
+                peer,
+                outcome,
+                FUEL - game_sample.fuel_left()
+            ),
+            Err(err) => eprintln!("{}: connection error: {}", peer, err),
+        }
+    });
+}
@@ -0,0 +1,479 @@
+use std::{collections::HashSet, sync::Arc, vec};
+
+use crate::a_star_algo::AStarAlgo;
+use crate::board::{Board, Content};
+use crate::direction::Direction;
+use crate::exploration;
+use crate::guards::Forecast;
+use crate::keys::KeySet;
+use crate::topology::Topology;
+use crate::protocol::GameSettings;
+use crate::tour;
+use crate::vect2_t::{UCoord2, UDelta2};
+
+// Number of stale squares tried when looking for one to check again.
+const STALE_SQUARE_TRIES: usize = 8;
+
+// Number of turns the patrols of the visible guards are forecast for.
+const GUARD_FORECAST_TURNS: usize = 32;
+
+// Solver holds the whole decision state of Kirk between two rounds.
+// It is fed with the round data and returns the next move, whatever the input source is.
+pub struct Solver {
+    board: Board,
+    exploration_engine: exploration::Engine,
+    a_star_algo: AStarAlgo,
+    returning_to_starting_point: bool,
+    return_path_iter: Option<vec::IntoIter<UCoord2>>,
+    recalculate_aproximate_return_path_at: Option<UCoord2>,
+    target_coord: Option<UCoord2>,
+    rounds_counter: usize,
+    max_rounds_left: usize,
+    planned_path: Vec<UCoord2>,
+    backtracking: bool,
+    cmd_rooms_to_visit: usize,
+    visited_cmd_rooms: Vec<UCoord2>,
+    // known rooms left to visit when the tour was computed, and the order chosen for them
+    tour_candidates: Vec<UCoord2>,
+    tour: Vec<UCoord2>,
+    // keys held at the last round, and the way to the next key to pick up or to a square to check again
+    keys: KeySet,
+    detour_path: Vec<UCoord2>,
+    // stale square being checked again, kept until reached
+    revisit_target: Option<UCoord2>,
+    // walls seen opening, they may lead to squares out of reach until then
+    opened_walls: Vec<UCoord2>,
+}
+
+impl Solver {
+    pub fn new(width: usize, height: usize, rounds: usize) -> Self {
+        Self {
+            board: Board::new(width, height, rounds),
+            exploration_engine: exploration::Engine::new(),
+            a_star_algo: AStarAlgo::new(),
+            returning_to_starting_point: false,
+            return_path_iter: None,
+            recalculate_aproximate_return_path_at: None,
+            target_coord: None,
+            rounds_counter: 0,
+            max_rounds_left: 0,
+            planned_path: Vec::new(),
+            backtracking: false,
+            cmd_rooms_to_visit: 1,
+            visited_cmd_rooms: Vec::new(),
+            tour_candidates: Vec::new(),
+            tour: Vec::new(),
+            keys: KeySet::new(),
+            detour_path: Vec::new(),
+            revisit_target: None,
+            opened_walls: Vec::new(),
+        }
+    }
+
+    pub fn from_settings(settings: &GameSettings) -> Self {
+        let mut solver = Self::new(settings.width, settings.height, settings.rounds);
+        solver.cmd_rooms_to_visit = settings.cmd_rooms;
+        solver
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn rounds_counter(&self) -> usize {
+        self.rounds_counter
+    }
+
+    pub fn is_returning(&self) -> bool {
+        self.returning_to_starting_point
+    }
+
+    // Squares Kirk intends to walk through, starting with the next move.
+    pub fn planned_path(&self) -> &[UCoord2] {
+        &self.planned_path
+    }
+
+    pub fn visited_cmd_rooms(&self) -> &[UCoord2] {
+        &self.visited_cmd_rooms
+    }
+
+    // True if the last move went back to a previous square because exploration was stuck.
+    pub fn is_backtracking(&self) -> bool {
+        self.backtracking
+    }
+
+    // Grid and moves of Kirk, the square grid with the four orthogonal moves of the CodinGame game unless told otherwise.
+    pub fn set_topology(&mut self, topology: Arc<dyn Topology>) {
+        self.board.set_topology(topology);
+    }
+
+    // Keep the knowledge as bitboards too, off by default : the frontier is faster but not the turns of whole games, see
+    // the game_turn benches.
+    pub fn set_bitboard(&mut self, enabled: bool) {
+        self.board.set_bitboard(enabled);
+    }
+
+    // The CodinGame move, see next_step.
+    pub fn next_move(&mut self, rick_coord: UCoord2, board_rows: &[String]) -> Option<Direction> {
+        self.next_step(rick_coord, board_rows).map(Direction::from)
+    }
+
+    // Move of the topology. Returns None when Kirk is back to the starting point, when there is no turn left
+    // or nowhere to go.
+    pub fn next_step(&mut self, rick_coord: UCoord2, board_rows: &[String]) -> Option<UDelta2> {
+        self.board.update_with(rick_coord, board_rows);
+        self.exploration_engine.set_square_covered(rick_coord);
+
+        self.rounds_counter += 1;
+        self.planned_path.clear();
+        self.detour_path.clear();
+        self.backtracking = false;
+
+        if self.board.keys() != self.keys {
+            // A door is open now, the dead ends found behind it may lead somewhere.
+            self.keys = self.board.keys();
+            self.exploration_engine.clear();
+            self.exploration_engine.set_square_covered(rick_coord);
+        }
+
+        if !self.board.changed_coords().is_empty() {
+            // Walls moved : the paths and dead ends found on the old knowledge are computed again.
+            self.return_path_iter = None;
+            self.recalculate_aproximate_return_path_at = None;
+            self.tour_candidates.clear();
+            self.exploration_engine.clear();
+            self.exploration_engine.set_square_covered(rick_coord);
+
+            let board = &self.board;
+            self.opened_walls
+                .extend(board.changed_coords().iter().filter(|coord| board.get_content(coord) == Content::Empty));
+        }
+        let board = &self.board;
+        self.opened_walls
+            .retain(|&coord| coord != rick_coord && board.get_content(&coord) == Content::Empty);
+
+        let on_new_cmd_room = self.board.cmd_room_coords().contains(&rick_coord)
+            && !self.visited_cmd_rooms.contains(&rick_coord);
+        if !self.returning_to_starting_point && on_new_cmd_room {
+            self.visited_cmd_rooms.push(rick_coord);
+
+            if self.visited_cmd_rooms.len() >= self.cmd_rooms_to_visit {
+                eprintln!("First part in {} turns", self.rounds_counter);
+                self.max_rounds_left = self.board.rounds() + 1;
+                self.returning_to_starting_point = true;
+                self.target_coord = self.board.rick_start_coord();
+            } else {
+                // A new exploration starts from this room, the squares covered to reach it may be needed again.
+                self.exploration_engine.clear();
+                self.exploration_engine.set_square_covered(rick_coord);
+            }
+        }
+
+        if self.recalculate_aproximate_return_path_at == Some(rick_coord) {
+            self.return_path_iter = None;
+        }
+
+        let next_coord = if self.returning_to_starting_point {
+            if self.max_rounds_left == 0 {
+                eprintln!("No turns left. Game Over");
+                return None;
+            }
+            self.max_rounds_left -= 1;
+
+            if Some(rick_coord) == self.board.rick_start_coord() {
+                return None;
+            }
+
+            // A path through unknown squares may turn out to pass beside a wall a diagonal move cannot cut.
+            let board = &self.board;
+            match self.return_path_iter.as_mut().map(Iterator::next) {
+                Some(Some(next_coord))
+                    if board
+                        .neighbours_in_board_iter(rick_coord, Direction::Left)
+                        .any(|coord| coord == next_coord) =>
+                {
+                    Some(next_coord)
+                }
+                Some(None) => None,
+                _ => self.start_return_path(rick_coord),
+            }
+        } else {
+            self.target_coord = self.next_cmd_room(rick_coord);
+
+            self.fetch_key(rick_coord)
+                .or_else(|| self.go_through_opened_wall(rick_coord))
+                .or_else(|| self.explore(rick_coord))
+                .or_else(|| self.revisit_stale_square(rick_coord))
+        };
+
+        let next_coord = self.avoid_guards(rick_coord, next_coord);
+
+        if let Some(next_coord) = next_coord {
+            match &self.return_path_iter {
+                Some(iter) if self.returning_to_starting_point => {
+                    self.planned_path.push(next_coord);
+                    self.planned_path.extend_from_slice(iter.as_slice());
+                }
+                _ if self.detour_path.first() == Some(&next_coord) => {
+                    self.planned_path.extend_from_slice(&self.detour_path);
+                }
+                _ if self.exploration_engine.path().last() == Some(&next_coord) => {
+                    self.planned_path
+                        .extend(self.exploration_engine.path().iter().rev());
+                }
+                _ => self.planned_path.push(next_coord),
+            }
+        }
+
+        next_coord.map(|next_coord| self.board.step_to(rick_coord, next_coord))
+    }
+
+    // First room of the best tour through the known rooms left to visit, computed again when a room is found or visited.
+    fn next_cmd_room(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        let rooms: Vec<_> = self
+            .board
+            .cmd_room_coords()
+            .iter()
+            .filter(|room| !self.visited_cmd_rooms.contains(room))
+            .copied()
+            .collect();
+
+        if rooms != self.tour_candidates {
+            self.tour = tour::best_tour(
+                &mut self.a_star_algo,
+                &self.board,
+                rick_coord,
+                &rooms,
+                self.cmd_rooms_to_visit - self.visited_cmd_rooms.len(),
+                self.board.rick_start_coord(),
+            );
+            self.tour_candidates = rooms;
+        }
+
+        self.tour.first().copied()
+    }
+
+    // A known key opening a known door is picked up before exploring further, the door may hide a control room.
+    fn fetch_key(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        let board = &self.board;
+        let a_star_algo = &mut self.a_star_algo;
+        let door_colors = board.door_colors();
+
+        self.detour_path = board
+            .key_coords()
+            .filter(|&(_, color)| door_colors.contains(color) && !board.keys().contains(color))
+            .map(|(coord, _)| {
+                a_star_algo.compute_path(board, rick_coord, coord, &[Content::Empty, Content::Unknown])
+            })
+            .filter(|path| !path.is_empty())
+            .min_by_key(|path| path.len())
+            .unwrap_or_default();
+
+        self.detour_path.first().copied()
+    }
+
+    // A wall that just opened is crossed before it closes again, the exploration goes on from there.
+    fn go_through_opened_wall(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        let board = &self.board;
+        let a_star_algo = &mut self.a_star_algo;
+
+        self.detour_path = self
+            .opened_walls
+            .iter()
+            .map(|&coord| a_star_algo.compute_path(board, rick_coord, coord, &[Content::Empty]))
+            .filter(|path| !path.is_empty())
+            .min_by_key(|path| path.len())
+            .unwrap_or_default();
+
+        self.detour_path.first().copied()
+    }
+
+    // With nowhere left to explore, the squares seen the longest time ago are checked again,
+    // in a changing labyrinth a wall may have opened since. The walls between known and unknown squares come first.
+    fn revisit_stale_square(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        // the exploration starts again from wherever this leads, without walking back its covered squares
+        self.exploration_engine.clear();
+
+        let board = &self.board;
+        let a_star_algo = &mut self.a_star_algo;
+        // the squares from where the radar still shows unknown squares come before all
+        let revealing: HashSet<_> = exploration::revealing_squares(board).into_iter().collect();
+
+        // a target is given up once the radar saw its unknown squares on the way, when others are left to see
+        let keeps_target =
+            |target: UCoord2| revealing.is_empty() || exploration::reveals_unknown(board, target);
+        if let Some(target) = self
+            .revisit_target
+            .filter(|&target| target != rick_coord && keeps_target(target))
+        {
+            self.detour_path = a_star_algo.compute_path(board, rick_coord, target, &[Content::Empty]);
+        }
+
+        if self.detour_path.is_empty() {
+            // a wall between coord and a square Kirk cannot reach now
+            let reachable = reachable_squares(board, rick_coord);
+            let faces_frontier_wall = |coord: UCoord2| {
+                Direction::array().iter().filter_map(|dir| board.neighbour(coord, dir.to_delta())).any(|wall| {
+                    board.get_content(&wall) == Content::Wall
+                        && board
+                            .neighbours_in_board_iter(wall, Direction::Left)
+                            .any(|behind| {
+                                board.get_content(&behind) != Content::Wall
+                                    && !reachable.contains(&behind)
+                            })
+                })
+            };
+            let mut squares: Vec<_> = reachable
+                .iter()
+                .copied()
+                .filter(|&coord| coord != rick_coord && board.get_content(&coord) == Content::Empty)
+                .filter_map(|coord| {
+                    let next_to_frontier = faces_frontier_wall(coord);
+                    board
+                        .last_seen(&coord)
+                        .map(|turn| (!revealing.contains(&coord), !next_to_frontier, turn, coord))
+                })
+                .collect();
+            squares.sort_by_key(|&(hides_nothing, far_from_frontier, turn, coord)| {
+                (hides_nothing, far_from_frontier, turn, coord.y, coord.x)
+            });
+
+            self.detour_path = squares
+                .iter()
+                .take(STALE_SQUARE_TRIES)
+                .map(|&(_, _, _, coord)| a_star_algo.compute_path(board, rick_coord, coord, &[Content::Empty]))
+                .find(|path| !path.is_empty())
+                .unwrap_or_default();
+            self.revisit_target = self.detour_path.last().copied();
+        }
+
+        self.detour_path.first().copied()
+    }
+
+    fn start_return_path(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        let board = &self.board;
+        let start_coord = board.rick_start_coord().unwrap();
+
+        let return_path_try =
+            self.a_star_algo
+                .compute_path(board, rick_coord, start_coord, &[Content::Empty]);
+        // an empty path means the start cannot be reached, a wall may have closed on the way
+        let return_path_to_test = if return_path_try.is_empty() || return_path_try.len() > self.max_rounds_left {
+            let approximate_return_path_try = self.a_star_algo.compute_path(
+                board,
+                rick_coord,
+                start_coord,
+                &[Content::Empty, Content::Unknown],
+            );
+            if approximate_return_path_try.len() <= self.max_rounds_left {
+                self.recalculate_aproximate_return_path_at = approximate_return_path_try
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, coord)| {
+                        if board.get_content(coord) == Content::Unknown {
+                            // i-2 -> the unknown become visible with the radar
+                            Some(approximate_return_path_try[i.saturating_sub(2)])
+                        } else {
+                            None
+                        }
+                    });
+            }
+            approximate_return_path_try
+        } else {
+            return_path_try
+        };
+
+        if !return_path_to_test.is_empty() && return_path_to_test.len() <= self.max_rounds_left {
+            let mut iter = return_path_to_test.into_iter();
+            let next_coord = iter.next();
+            self.return_path_iter = Some(iter);
+            next_coord
+        } else {
+            eprintln!("Best path not found ! Try exploration one time.");
+
+            self.go_through_opened_wall(rick_coord)
+                .or_else(|| self.explore(rick_coord))
+                .or_else(|| self.revisit_stale_square(rick_coord))
+        }
+    }
+
+    // The visible guards keep their patrol. A move meeting one of them is replaced by the first step of a space-time
+    // path to the same target, or else by any safe move : Kirk cannot wait in place.
+    fn avoid_guards(&mut self, rick_coord: UCoord2, next_coord: Option<UCoord2>) -> Option<UCoord2> {
+        let board = &self.board;
+        if board.guards().is_empty() {
+            return next_coord;
+        }
+
+        let forecast = Forecast::new(board.guards(), GUARD_FORECAST_TURNS, |coord| {
+            coord.x < board.width()
+                && coord.y < board.height()
+                && matches!(board.get_content(&coord), Content::Empty | Content::Unknown)
+        });
+        let wanted_coord = next_coord?;
+        if forecast.is_safe(wanted_coord, 1) {
+            return next_coord;
+        }
+
+        let target_coord = match self.return_path_iter.as_ref() {
+            Some(iter) if self.returning_to_starting_point => iter.as_slice().last().copied().unwrap_or(wanted_coord),
+            _ => wanted_coord,
+        };
+        let path = self.a_star_algo.compute_path_in_time(
+            board,
+            rick_coord,
+            target_coord,
+            &[Content::Empty],
+            forecast.turns(),
+            |coord, turn| forecast.is_safe(coord, turn),
+        );
+        let safe_coord = path.first().copied().or_else(|| {
+            board.neighbours_in_board_iter(rick_coord, Direction::Left).find(|&coord| {
+                board.get_content(&coord).is_walkable(&[Content::Empty], board.keys()) && forecast.is_safe(coord, 1)
+            })
+        });
+
+        // The plans made without the guards are made again from the new square.
+        self.return_path_iter = None;
+        self.detour_path = path;
+        safe_coord.or(next_coord)
+    }
+
+    fn explore(&mut self, rick_coord: UCoord2) -> Option<UCoord2> {
+        let _search_result = self.exploration_engine.start_look_forward(
+            &self.board,
+            rick_coord,
+            &self.target_coord,
+            100,
+        );
+        // With no way forward, the exploration engine falls back to a previous covered square.
+        self.backtracking = self.exploration_engine.path().is_empty();
+        self.exploration_engine
+            .get_next_coord(&self.board, rick_coord)
+    }
+}
+
+// Known squares Kirk can walk to from start, in breadth first order, or row by row from the bitboards.
+fn reachable_squares(board: &Board, start: UCoord2) -> Vec<UCoord2> {
+    if let Some(bits) = board.exact_bits() {
+        return bits.reachable(start).coords().collect();
+    }
+
+    let mut reachable = vec![start];
+    let mut seen = HashSet::new();
+    seen.insert(start);
+
+    let mut idx = 0;
+    while let Some(&coord) = reachable.get(idx) {
+        for neighbour in board.neighbours_in_board_iter(coord, Direction::Left) {
+            if board.get_content(&neighbour).is_walkable(&[Content::Empty], board.keys())
+                && seen.insert(neighbour)
+            {
+                reachable.push(neighbour);
+            }
+        }
+        idx += 1;
+    }
+
+    reachable
+}
@@ -0,0 +1,205 @@
+use std::collections::{HashSet, VecDeque};
+#[cfg(any(feature = "simulator", feature = "render"))]
+use std::fmt;
+
+use crate::grid::Grid;
+use crate::keys::{KeyColor, KeySet};
+#[cfg(any(feature = "simulator", feature = "render"))]
+use crate::replay::Replay;
+use crate::topology::Topology;
+use crate::vect2_t::UCoord2;
+
+// Statistics about how wasteful a recorded game was.
+#[cfg(any(feature = "simulator", feature = "render"))]
+#[derive(Debug, Clone)]
+pub struct GameStats {
+    pub moves: usize,
+    pub visits: Grid<usize>,
+    pub squares_visited: usize,
+    pub revisits: usize,
+    pub max_visits: usize,
+    pub cmd_room_found_turn: Option<usize>,
+    pub revealed_when_cmd_room_found: Option<f64>,
+    pub alarm_turn: Option<usize>,
+    pub backtracking_steps: usize,
+    pub return_length: Option<usize>,
+    pub optimal_return_length: Option<usize>,
+}
+
+#[cfg(any(feature = "simulator", feature = "render"))]
+impl GameStats {
+    pub fn from_replay(replay: &Replay) -> Self {
+        let trajectory = replay.trajectory();
+
+        let mut visits = Grid::new(replay.width(), replay.height(), 0);
+        for &coord in &trajectory {
+            visits[coord] += 1;
+        }
+        let counts = || {
+            visits
+                .iter()
+                .map(|(_, count)| count)
+                .filter(|&&count| count > 0)
+        };
+        let squares_visited = counts().count();
+        let revisits = counts().map(|&count| count - 1).sum();
+        let max_visits = counts().copied().max().unwrap_or(0);
+
+        // the turn the radar has shown as many control rooms as Kirk has to visit
+        let cmd_room_coords = replay.cmd_room_coords();
+        let mut cmd_rooms_revealed = 0;
+        let cmd_room_found_turn = replay.turns().iter().position(|turn| {
+            cmd_rooms_revealed += turn
+                .revealed
+                .iter()
+                .filter(|coord| cmd_room_coords.contains(coord))
+                .count();
+            cmd_rooms_revealed > 0 && cmd_rooms_revealed >= replay.cmd_rooms_to_visit()
+        });
+        let revealed_when_cmd_room_found = cmd_room_found_turn.map(|found_turn| {
+            let revealed: usize = replay.turns()[..=found_turn]
+                .iter()
+                .map(|turn| turn.revealed.len())
+                .sum();
+            100. * revealed as f64 / (replay.width() * replay.height()) as f64
+        });
+
+        let backtracking_steps = replay
+            .turns()
+            .iter()
+            .filter(|turn| turn.backtracking)
+            .count();
+
+        let alarm_turn = replay.alarm_turn();
+        let return_length = alarm_turn.map(|alarm_turn| trajectory.len() - 1 - alarm_turn);
+        let optimal_return_length = alarm_turn.and_then(|alarm_turn| {
+            let keys = trajectory[..=alarm_turn]
+                .iter()
+                .filter_map(|&coord| KeyColor::from_key_byte(replay.cell(coord)))
+                .fold(KeySet::new(), KeySet::with);
+            shortest_distance(
+                replay.map(),
+                replay.topology().as_ref(),
+                trajectory[alarm_turn],
+                keys,
+                replay.start(),
+            )
+        });
+
+        Self {
+            moves: trajectory.len().saturating_sub(1),
+            visits,
+            squares_visited,
+            revisits,
+            max_visits,
+            cmd_room_found_turn,
+            revealed_when_cmd_room_found,
+            alarm_turn,
+            backtracking_steps,
+            return_length,
+            optimal_return_length,
+        }
+    }
+
+    // Actual return length over the optimal one, 1.0 is a perfect return.
+    pub fn return_ratio(&self) -> Option<f64> {
+        match (self.return_length, self.optimal_return_length) {
+            (Some(actual), Some(optimal)) if optimal > 0 => Some(actual as f64 / optimal as f64),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(feature = "simulator", feature = "render"))]
+fn or_dash<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("-".to_owned(), |v| v.to_string())
+}
+
+#[cfg(any(feature = "simulator", feature = "render"))]
+impl fmt::Display for GameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("moves", self.moves.to_string()),
+            ("squares visited", self.squares_visited.to_string()),
+            ("revisits", self.revisits.to_string()),
+            ("max visits of a square", self.max_visits.to_string()),
+            ("backtracking steps", self.backtracking_steps.to_string()),
+            ("C revealed at turn", or_dash(self.cmd_room_found_turn)),
+            (
+                "map revealed when C found",
+                or_dash(
+                    self.revealed_when_cmd_room_found
+                        .map(|p| format!("{:.1}%", p)),
+                ),
+            ),
+            ("C reached at turn", or_dash(self.alarm_turn)),
+            ("return length", or_dash(self.return_length)),
+            ("optimal C->T distance", or_dash(self.optimal_return_length)),
+            (
+                "return ratio",
+                or_dash(self.return_ratio().map(|r| format!("{:.2}", r))),
+            ),
+        ];
+
+        for (label, value) in rows.iter() {
+            writeln!(f, "{:<26} {:>8}", label, value)?;
+        }
+        Ok(())
+    }
+}
+
+// The other square with the same digit, in the file format.
+pub fn portal_exit(map: &Grid<u8>, coord: UCoord2) -> Option<UCoord2> {
+    let label = *map.get(coord)?;
+    if !label.is_ascii_digit() {
+        return None;
+    }
+    map.iter()
+        .find(|&(other, &cell)| cell == label && other != coord)
+        .map(|(other, _)| other)
+}
+
+// Fewest moves from start to target with the rules of the referee, on a labyrinth in the file format: the moves of
+// the topology, no walls nor hazards, portals crossed at once, and doors opened by the keys held at start or picked
+// up on the way.
+pub fn shortest_distance(
+    map: &Grid<u8>,
+    topology: &dyn Topology,
+    start: UCoord2,
+    keys: KeySet,
+    target: UCoord2,
+) -> Option<usize> {
+    let (width, height) = (map.width(), map.height());
+    let cell = |coord: UCoord2| map[coord];
+    let is_blocked = |coord: UCoord2| map.get(coord).is_none_or(|&square| square == b'#');
+
+    let mut visited = HashSet::new();
+    visited.insert((start, keys));
+    let mut queue = VecDeque::new();
+    queue.push_back((start, keys, 0));
+
+    while let Some((coord, keys, distance)) = queue.pop_front() {
+        if coord == target {
+            return Some(distance);
+        }
+        for &delta in topology.deltas(coord) {
+            let neighbour = match topology.neighbour(coord, delta, width, height) {
+                Some(neighbour) if topology.allows(coord, delta, &is_blocked) => neighbour,
+                _ => continue,
+            };
+            let content = cell(neighbour);
+            let locked =
+                KeyColor::from_door_byte(content).is_some_and(|color| !keys.contains(color));
+            if matches!(content, b'#' | b'~') || locked {
+                continue;
+            }
+            let keys = KeyColor::from_key_byte(content).map_or(keys, |color| keys.with(color));
+            let neighbour = portal_exit(map, neighbour).unwrap_or(neighbour);
+            if visited.insert((neighbour, keys)) {
+                queue.push_back((neighbour, keys, distance + 1));
+            }
+        }
+    }
+
+    None
+}
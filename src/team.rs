@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use crate::a_star_algo::AStarAlgo;
+use crate::board::{Board, Content};
+use crate::exploration;
+use crate::topology::Topology;
+use crate::protocol::GameSettings;
+use crate::vect2_t::{UCoord2, UDelta2};
+
+// Team coordinates several explorers sharing one board. Each round it merges their radar views, then gives each
+// explorer a target : a frontier square of its own while the control room is unknown, then the room for the
+// nearest explorer and the starting point for the others.
+pub struct Team {
+    board: Board,
+    a_star_algo: AStarAlgo,
+    starts: Vec<UCoord2>,
+    targets: Vec<Option<UCoord2>>,
+    returning: bool,
+}
+
+impl Team {
+    pub fn new(width: usize, height: usize, rounds: usize, starts: Vec<UCoord2>) -> Self {
+        Self {
+            board: Board::new(width, height, rounds),
+            a_star_algo: AStarAlgo::new(),
+            targets: vec![None; starts.len()],
+            starts,
+            returning: false,
+        }
+    }
+
+    pub fn from_settings(settings: &GameSettings, starts: Vec<UCoord2>) -> Self {
+        Self::new(settings.width, settings.height, settings.rounds, starts)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // Square each explorer is heading to, None for an explorer with nothing left to do.
+    pub fn targets(&self) -> &[Option<UCoord2>] {
+        &self.targets
+    }
+
+    // True once an explorer reached the control room and all of them go back to their starting point.
+    pub fn is_returning(&self) -> bool {
+        self.returning
+    }
+
+    // Grid and moves of the explorers, the square grid with the four orthogonal moves unless told otherwise.
+    pub fn set_topology(&mut self, topology: Arc<dyn Topology>) {
+        self.board.set_topology(topology);
+    }
+
+    // One move of the topology per explorer, None to stay in place.
+    pub fn next_moves(
+        &mut self,
+        coords: &[UCoord2],
+        views: &[Vec<String>],
+    ) -> Vec<Option<UDelta2>> {
+        self.board.begin_update();
+        for (&coord, view) in coords.iter().zip(views) {
+            self.board.merge_view(coord, view);
+        }
+
+        let cmd_room_coord = self.board.cmd_room_coord();
+        if cmd_room_coord.is_some_and(|room| coords.contains(&room)) {
+            self.returning = true;
+        }
+
+        self.targets = if self.returning {
+            self.starts.iter().copied().map(Some).collect()
+        } else {
+            match cmd_room_coord
+                .and_then(|room| self.nearest_explorer(coords, room).map(|idx| (idx, room)))
+            {
+                Some((idx, room)) => {
+                    let mut targets: Vec<_> = self.starts.iter().copied().map(Some).collect();
+                    targets[idx] = Some(room);
+                    targets
+                }
+                None => exploration::assign_frontier_targets(&self.board, coords),
+            }
+        };
+
+        coords
+            .iter()
+            .zip(self.targets.clone())
+            .map(|(&coord, target)| {
+                let target = target.filter(|&target| target != coord)?;
+                self.next_coord(coord, target)
+                    .map(|next_coord| self.board.step_to(coord, next_coord))
+            })
+            .collect()
+    }
+
+    // The explorer with the shortest known path to the target.
+    fn nearest_explorer(&self, coords: &[UCoord2], target: UCoord2) -> Option<usize> {
+        coords
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, &coord)| {
+                exploration::walking_distances(&self.board, coord)[target]
+                    .map(|distance| (distance, idx))
+            })
+            .min()
+            .map(|(_, idx)| idx)
+    }
+
+    // First step of the known path, or of a path through unknown squares assumed free.
+    fn next_coord(&mut self, coord: UCoord2, target: UCoord2) -> Option<UCoord2> {
+        let board = &self.board;
+        let path = self
+            .a_star_algo
+            .compute_path(board, coord, target, &[Content::Empty]);
+        let path = if path.is_empty() {
+            self.a_star_algo
+                .compute_path(board, coord, target, &[Content::Empty, Content::Unknown])
+        } else {
+            path
+        };
+        path.first().copied()
+    }
+}
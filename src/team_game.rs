@@ -0,0 +1,188 @@
+use std::{fs, iter, str::from_utf8, sync::Arc};
+
+use crate::game_sample::{Explorer, GameSample, Outcome, SampleError, World, FUEL};
+use crate::protocol::GameSettings;
+use crate::team::Team;
+use crate::vect2_t::{UCoord2, UDelta2};
+
+// TeamGame referees several explorers in the same world, each of them with its own radar view and keys. The header option `agents=<N>` puts N explorers on the starting square of the header, every other `T`
+// square of the map adds an explorer starting there.
+// The alarm is triggered by the first explorer reaching the control room, the game is won when all of them are back
+// to their starting point. The guards step and the walls of a changing labyrinth toggle once a round, after all the
+// explorers moved.
+pub struct TeamGame {
+    world: World,
+    explorers: Vec<Explorer>,
+    settings: GameSettings,
+    moves: usize,
+    alarm_turn: Option<usize>,
+    alarm_rounds_left: Option<usize>,
+    outcome: Option<Outcome>,
+}
+
+impl TeamGame {
+    pub fn new(filename: &str) -> Result<Self, SampleError> {
+        Self::from_sample_string(&fs::read_to_string(filename)?)
+    }
+
+    pub fn from_sample_string(sample: &str) -> Result<Self, SampleError> {
+        let game_sample = GameSample::from_sample_string(sample)?;
+        let header = sample.lines().next().unwrap_or_default();
+        let agents = header
+            .split_whitespace()
+            .find_map(|option| option.strip_prefix("agents="))
+            .map_or(Ok(1), str::parse)
+            .map_err(|_| SampleError::MalformedHeader(header.to_owned()))?;
+        let settings = game_sample.settings();
+        let start = game_sample.start();
+        let world = game_sample.into_world();
+
+        let other_starts = world
+            .map()
+            .iter()
+            .filter(|&(coord, &square)| square == b'T' && coord != start)
+            .map(|(coord, _)| coord);
+        let explorers = iter::repeat_n(start, agents)
+            .chain(other_starts)
+            .map(|start| Explorer::new(&world, start))
+            .collect();
+
+        Ok(Self {
+            world,
+            explorers,
+            settings,
+            moves: 0,
+            alarm_turn: None,
+            alarm_rounds_left: None,
+            outcome: None,
+        })
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    // The round after which the alarm was triggered.
+    pub fn alarm_turn(&self) -> Option<usize> {
+        self.alarm_turn
+    }
+
+    pub fn starts(&self) -> Vec<UCoord2> {
+        self.explorers.iter().map(Explorer::start).collect()
+    }
+
+    pub fn coords(&self) -> Vec<UCoord2> {
+        self.explorers.iter().map(Explorer::current).collect()
+    }
+
+    pub fn explorers(&self) -> &[Explorer] {
+        &self.explorers
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn settings(&self) -> GameSettings {
+        self.settings
+    }
+
+    // The radar rows of each explorer for the next round.
+    pub fn round_views(&mut self) -> Vec<Vec<String>> {
+        let world = &self.world;
+        self.explorers
+            .iter_mut()
+            .map(|explorer| {
+                explorer.update_view(world);
+                explorer
+                    .view()
+                    .rows()
+                    .map(|row| from_utf8(row).unwrap().to_owned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    // One move per explorer, None to stay in place.
+    pub fn set_actions(&mut self, actions: &[Option<UDelta2>]) {
+        if self.outcome.is_some() {
+            return;
+        }
+
+        if self.alarm_rounds_left.is_none() && actions.iter().all(Option::is_none) {
+            self.outcome = Some(Outcome::NoAction);
+            return;
+        }
+
+        // every explorer moves in the world as it was at the start of the round
+        for (explorer, &action) in self.explorers.iter_mut().zip(actions) {
+            let delta = match action {
+                Some(delta) => delta,
+                None => continue,
+            };
+            if !self.world.is_move(explorer.current(), delta) {
+                self.outcome = Some(Outcome::InvalidAction);
+                return;
+            }
+            if let Err(outcome) = explorer.step(&self.world, delta) {
+                self.outcome = Some(outcome);
+                return;
+            }
+        }
+
+        self.moves += 1;
+        let coords = self.coords();
+
+        // an explorer is caught on a guard's square, crossing a guard included, or when a guard reaches its square
+        let caught = |world: &World| coords.iter().any(|&coord| world.guard_on(coord));
+        if caught(&self.world) {
+            self.outcome = Some(Outcome::Caught);
+            return;
+        }
+        self.world.advance(&coords);
+        if caught(&self.world) {
+            self.outcome = Some(Outcome::Caught);
+            return;
+        }
+
+        let on_cmd_room = coords.iter().any(|&coord| self.world.map()[coord] == b'C');
+        let all_home = coords == self.starts();
+        match &mut self.alarm_rounds_left {
+            None if on_cmd_room => {
+                self.alarm_turn = Some(self.moves);
+                self.alarm_rounds_left = Some(self.settings.rounds);
+            }
+            None => (),
+            Some(rounds_left) => {
+                *rounds_left = rounds_left.saturating_sub(1);
+                if all_home {
+                    self.outcome = Some(Outcome::Won);
+                } else if *rounds_left == 0 {
+                    self.outcome = Some(Outcome::AlarmExpired);
+                }
+            }
+        }
+
+        if self.outcome.is_none() && self.moves == FUEL {
+            self.outcome = Some(Outcome::OutOfFuel);
+        }
+    }
+
+    // Play a whole game with a team of explorers coordinated by Team, returned to inspect its final knowledge.
+    pub fn play(&mut self) -> Team {
+        let mut team = Team::from_settings(&self.settings(), self.starts());
+        team.set_topology(Arc::clone(self.world.topology()));
+
+        while self.outcome.is_none() {
+            let views = self.round_views();
+            let actions = team.next_moves(&self.coords(), &views);
+            self.set_actions(&actions);
+        }
+
+        team
+    }
+}
@@ -0,0 +1,239 @@
+use std::{fmt, sync::Arc};
+
+use crate::movement::{self, Movement};
+use crate::vect2_t::{Bounds, UCoord2, UDelta2};
+
+// The shape of the grid : which squares are next to a square, how far two squares are, and how moves are written.
+// The square grid with its movement models, the torus and the hexagonal grid share the same board, planners and referee.
+pub trait Topology: fmt::Debug + Send + Sync {
+    // Moves from a square, in the order the planners try them.
+    fn deltas(&self, coord: UCoord2) -> &[UDelta2];
+
+    // Whether a move is possible with these squares blocked, for moves passing beside other squares.
+    fn allows(
+        &self,
+        _from: UCoord2,
+        _delta: UDelta2,
+        _is_blocked: &dyn Fn(UCoord2) -> bool,
+    ) -> bool {
+        true
+    }
+
+    // Lower bound of the number of moves between two squares, the A* heuristic.
+    fn distance(&self, from: UCoord2, to: UCoord2) -> i32;
+
+    fn label(&self, from: UCoord2, delta: UDelta2) -> String;
+
+    fn parse_label(&self, from: UCoord2, label: &str) -> Option<UDelta2>;
+
+    // Square reached by a move on a width × height grid, None when the move leaves the grid.
+    fn neighbour(
+        &self,
+        from: UCoord2,
+        delta: UDelta2,
+        width: usize,
+        height: usize,
+    ) -> Option<UCoord2> {
+        from.neighbour_in(delta, Bounds::new(width, height))
+    }
+
+    // Whether moves and the radar window wrap around the edges of the grid.
+    fn wraps(&self) -> bool {
+        false
+    }
+}
+
+impl Topology for Movement {
+    fn deltas(&self, _coord: UCoord2) -> &[UDelta2] {
+        Movement::deltas(self)
+    }
+
+    fn allows(&self, from: UCoord2, delta: UDelta2, is_blocked: &dyn Fn(UCoord2) -> bool) -> bool {
+        Movement::allows(self, from, delta, is_blocked)
+    }
+
+    fn distance(&self, from: UCoord2, to: UCoord2) -> i32 {
+        self.heuristic(to - from)
+    }
+
+    fn label(&self, _from: UCoord2, delta: UDelta2) -> String {
+        movement::label(delta)
+    }
+
+    fn parse_label(&self, _from: UCoord2, label: &str) -> Option<UDelta2> {
+        movement::from_label(label)
+    }
+}
+
+// Square grid wrapping around its edges, a torus : moving off one edge enters the opposite one, with any movement
+// model. The border of the map is not a wall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Torus {
+    movement: Movement,
+    width: usize,
+    height: usize,
+}
+
+impl Torus {
+    pub fn new(movement: Movement, width: usize, height: usize) -> Self {
+        Self {
+            movement,
+            width,
+            height,
+        }
+    }
+
+    pub fn movement(&self) -> &Movement {
+        &self.movement
+    }
+
+    // The shortest of the deltas between two squares, around the edges or not.
+    pub fn wrapped_delta(&self, from: UCoord2, to: UCoord2) -> UDelta2 {
+        let wrap = |delta: i32, size: usize| {
+            let size = size as i32;
+            let delta = delta.rem_euclid(size);
+            if delta > size / 2 {
+                delta - size
+            } else {
+                delta
+            }
+        };
+        let delta = to - from;
+        UDelta2::new(wrap(delta.x, self.width), wrap(delta.y, self.height))
+    }
+
+    fn wrap(&self, from: UCoord2, delta: UDelta2) -> UCoord2 {
+        UCoord2::new(
+            (from.x as i64 + delta.x as i64).rem_euclid(self.width as i64) as usize,
+            (from.y as i64 + delta.y as i64).rem_euclid(self.height as i64) as usize,
+        )
+    }
+}
+
+impl Topology for Torus {
+    fn deltas(&self, _coord: UCoord2) -> &[UDelta2] {
+        self.movement.deltas()
+    }
+
+    fn allows(&self, from: UCoord2, delta: UDelta2, is_blocked: &dyn Fn(UCoord2) -> bool) -> bool {
+        self.movement
+            .allows_beside(delta, |beside| is_blocked(self.wrap(from, beside)))
+    }
+
+    fn distance(&self, from: UCoord2, to: UCoord2) -> i32 {
+        self.movement.heuristic(self.wrapped_delta(from, to))
+    }
+
+    fn label(&self, _from: UCoord2, delta: UDelta2) -> String {
+        movement::label(delta)
+    }
+
+    fn parse_label(&self, _from: UCoord2, label: &str) -> Option<UDelta2> {
+        movement::from_label(label)
+    }
+
+    // Every move stays on the torus.
+    fn neighbour(
+        &self,
+        from: UCoord2,
+        delta: UDelta2,
+        _width: usize,
+        _height: usize,
+    ) -> Option<UCoord2> {
+        Some(self.wrap(from, delta))
+    }
+
+    fn wraps(&self) -> bool {
+        true
+    }
+}
+
+const HEX_LABELS: [&str; 6] = ["W", "NW", "NE", "E", "SE", "SW"];
+
+// Hexagonal grid with pointy top cells in "odd-r" offset coordinates : odd rows are shifted half a cell to the right,
+// so the neighbours above and below depend on the parity of the row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HexGrid {
+    even_row_deltas: Vec<UDelta2>,
+    odd_row_deltas: Vec<UDelta2>,
+}
+
+impl Default for HexGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HexGrid {
+    pub fn new() -> Self {
+        let deltas = |deltas: [(i32, i32); 6]| deltas.iter().map(UDelta2::from).collect();
+        Self {
+            even_row_deltas: deltas([(-1, 0), (-1, -1), (0, -1), (1, 0), (0, 1), (-1, 1)]),
+            odd_row_deltas: deltas([(-1, 0), (0, -1), (1, -1), (1, 0), (1, 1), (0, 1)]),
+        }
+    }
+
+    // Axial coordinates, where the hex distance is easy to compute.
+    fn axial(coord: UCoord2) -> (i32, i32) {
+        let (x, y) = (coord.x as i32, coord.y as i32);
+        (x - (y - (y & 1)) / 2, y)
+    }
+}
+
+impl Topology for HexGrid {
+    fn deltas(&self, coord: UCoord2) -> &[UDelta2] {
+        if coord.y.is_multiple_of(2) {
+            &self.even_row_deltas
+        } else {
+            &self.odd_row_deltas
+        }
+    }
+
+    fn distance(&self, from: UCoord2, to: UCoord2) -> i32 {
+        let (from_q, from_r) = Self::axial(from);
+        let (to_q, to_r) = Self::axial(to);
+        let (dq, dr) = (to_q - from_q, to_r - from_r);
+        (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+    }
+
+    // Compass labels : W, NW, NE, E, SE and SW.
+    fn label(&self, from: UCoord2, delta: UDelta2) -> String {
+        match self
+            .deltas(from)
+            .iter()
+            .position(|&hex_delta| hex_delta == delta)
+        {
+            Some(idx) => HEX_LABELS[idx].to_owned(),
+            None => movement::label(delta),
+        }
+    }
+
+    fn parse_label(&self, from: UCoord2, label: &str) -> Option<UDelta2> {
+        let idx = HEX_LABELS
+            .iter()
+            .position(|&hex_label| hex_label == label)?;
+        Some(self.deltas(from)[idx])
+    }
+}
+
+// Topology of the header options "grid=<square|hex|torus>" and "moves=..." for a width × height map, the four
+// CodinGame moves on the square grid without them.
+pub fn from_options(
+    grid: Option<&str>,
+    moves: Option<&str>,
+    width: usize,
+    height: usize,
+) -> Option<Arc<dyn Topology>> {
+    match (grid, moves) {
+        (Some("hex"), None) => Some(Arc::new(HexGrid::new())),
+        (Some("torus"), moves) => {
+            let movement = moves.map_or(Some(Movement::orthogonal()), Movement::parse)?;
+            Some(Arc::new(Torus::new(movement, width, height)))
+        }
+        (None, Some(moves)) | (Some("square"), Some(moves)) => {
+            Some(Arc::new(Movement::parse(moves)?))
+        }
+        (None, None) | (Some("square"), None) => Some(Arc::new(Movement::orthogonal())),
+        _ => None,
+    }
+}
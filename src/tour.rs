@@ -0,0 +1,117 @@
+use crate::a_star_algo::AStarAlgo;
+use crate::board::{Board, Content};
+use crate::vect2_t::UCoord2;
+
+// Above this number of candidate rooms the orders are not all tried, the nearest room is taken at each step.
+const EXHAUSTIVE_SEARCH_LIMIT: usize = 7;
+
+// Cost of a leg without any known path, bigger than any real path.
+const UNREACHABLE: usize = usize::MAX / 1024;
+
+// Order in which to visit count of the rooms, starting from start and ending at end (the starting point of Kirk
+// in the multiple rooms variant), with the shortest total length. The legs are A* paths where unknown squares are
+// assumed free, the same optimistic guess as the approximate return path.
+pub fn best_tour(
+    a_star_algo: &mut AStarAlgo,
+    board: &Board,
+    start: UCoord2,
+    rooms: &[UCoord2],
+    count: usize,
+    end: Option<UCoord2>,
+) -> Vec<UCoord2> {
+    let count = count.min(rooms.len());
+    if count == 0 {
+        return Vec::new();
+    }
+
+    // distances[i][j] from point i to point j, start is the last point
+    let points: Vec<_> = rooms.iter().copied().chain(Some(start)).collect();
+    let mut distance = |from: UCoord2, to: UCoord2| {
+        if from == to {
+            0
+        } else {
+            match a_star_algo.compute_path(board, from, to, &[Content::Empty, Content::Unknown]) {
+                path if path.is_empty() => UNREACHABLE,
+                path => path.len(),
+            }
+        }
+    };
+    let distances: Vec<Vec<usize>> = points
+        .iter()
+        .map(|&from| rooms.iter().map(|&to| distance(from, to)).collect())
+        .collect();
+    let end_distances: Vec<usize> = rooms
+        .iter()
+        .map(|&room| end.map_or(0, |end| distance(room, end)))
+        .collect();
+
+    let start_idx = rooms.len();
+    let order = if rooms.len() <= EXHAUSTIVE_SEARCH_LIMIT {
+        let mut best = (usize::MAX, Vec::new());
+        let mut order = Vec::with_capacity(count);
+        search(
+            &distances,
+            &end_distances,
+            start_idx,
+            count,
+            &mut order,
+            0,
+            &mut best,
+        );
+        best.1
+    } else {
+        nearest_first(&distances, start_idx, count)
+    };
+
+    order.into_iter().map(|idx| rooms[idx]).collect()
+}
+
+// Depth first search of every order, cut as soon as the length exceeds the best one found.
+fn search(
+    distances: &[Vec<usize>],
+    end_distances: &[usize],
+    from: usize,
+    count: usize,
+    order: &mut Vec<usize>,
+    length: usize,
+    best: &mut (usize, Vec<usize>),
+) {
+    if order.len() == count {
+        let length = length + end_distances[from];
+        if length < best.0 {
+            *best = (length, order.clone());
+        }
+        return;
+    }
+
+    for room in 0..end_distances.len() {
+        let leg_length = length + distances[from][room];
+        if !order.contains(&room) && leg_length < best.0 {
+            order.push(room);
+            search(
+                distances,
+                end_distances,
+                room,
+                count,
+                order,
+                leg_length,
+                best,
+            );
+            order.pop();
+        }
+    }
+}
+
+fn nearest_first(distances: &[Vec<usize>], start_idx: usize, count: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = Vec::with_capacity(count);
+    let mut from = start_idx;
+    while order.len() < count {
+        let next = (0..start_idx)
+            .filter(|room| !order.contains(room))
+            .min_by_key(|&room| distances[from][room])
+            .unwrap();
+        order.push(next);
+        from = next;
+    }
+    order
+}
@@ -40,18 +40,6 @@ where
         self.x == x && self.y == y
     }
 
-    pub fn add(mut self, rhs: Self) -> Self {
-        self.x += rhs.x;
-        self.y += rhs.y;
-        self
-    }
-
-    pub fn sub(mut self, rhs: Self) -> Self {
-        self.x -= rhs.x;
-        self.y -= rhs.y;
-        self
-    }
-
     pub fn length2(&self) -> T {
         self.x * self.x + self.y * self.y
     }
@@ -90,10 +78,6 @@ where
 // coord2_int_idx!(usize);
 
 impl Vect2<f64> {
-    fn length_optim(&self) -> f64 {
-        self.x.hypot(self.y)
-    }
-
     pub fn x_usize(&self) -> usize {
         if self.x.is_sign_negative() {
             panic!()
@@ -166,14 +150,68 @@ vect2_from!(usize, isize);
 // vect2_from!(isize, isize);
 
 
+// Width and height of a grid, whose squares go from (0,0) to (width - 1,height - 1).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    width: usize,
+    height: usize,
+}
+
+impl Bounds {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, coord: UCoord2) -> bool {
+        coord.x < self.width && coord.y < self.height
+    }
+
+    // Every square, row by row.
+    pub fn coords(self) -> impl Iterator<Item = UCoord2> {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| UCoord2::new(x, y)))
+    }
+}
+
+impl UCoord2 {
+    // None when the delta takes the coordinate below zero.
+    pub fn checked_add_delta(self, delta: UDelta2) -> Option<UCoord2> {
+        let checked_add = |value: usize, delta: i32| {
+            if delta < 0 {
+                value.checked_sub(delta.unsigned_abs() as usize)
+            } else {
+                value.checked_add(delta as usize)
+            }
+        };
+        Some(Self {
+            x: checked_add(self.x, delta.x)?,
+            y: checked_add(self.y, delta.y)?,
+        })
+    }
+
+    // None when the delta leaves the grid, on maps without walls on their border too.
+    pub fn neighbour_in(self, delta: UDelta2, bounds: Bounds) -> Option<UCoord2> {
+        self.checked_add_delta(delta)
+            .filter(|&coord| bounds.contains(coord))
+    }
+}
+
+// Only for deltas known to stay on the grid : a coordinate below zero is a bug of the caller, see checked_add_delta.
 impl ops::Add<UDelta2> for UCoord2
 {
     type Output = Self;
 
     fn add(self, rhs: UDelta2) -> Self::Output {
-        Self {
-            x: (self.x as i32 + rhs.x) as usize,
-            y: (self.y as i32 + rhs.y) as usize,
+        match self.checked_add_delta(rhs) {
+            Some(coord) => coord,
+            None => panic!("{} + {} is below zero", self, rhs),
         }
     }
 }
@@ -0,0 +1,286 @@
+// Randomized checks of AStarAlgo::compute_path against a breadth first search, on seeded labyrinths.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::generator::Labyrinth;
+use the_labyrinth::vect2_t::UCoord2;
+
+const SEEDS: u64 = 200;
+
+fn random_labyrinth(rng: &mut StdRng) -> Labyrinth {
+    let width = rng.gen_range(5, 41);
+    let height = rng.gen_range(5, 25);
+    let loop_ratio = rng.gen_range(0., 0.5);
+    Labyrinth::generate(rng, width, height, loop_ratio)
+}
+
+fn free_squares(board: &Board) -> Vec<UCoord2> {
+    (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| UCoord2::new(x, y)))
+        .filter(|coord| board.get_content(coord) == Content::Empty)
+        .collect()
+}
+
+// Breadth first search over the rows of the labyrinth, written apart from the crate's searches.
+fn bfs_distance(rows: &[Vec<u8>], start: UCoord2, target: UCoord2) -> Option<usize> {
+    let mut distances = vec![vec![None; rows[0].len()]; rows.len()];
+    distances[start.y][start.x] = Some(0);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(coord) = queue.pop_front() {
+        let distance = distances[coord.y][coord.x].unwrap();
+        if coord == target {
+            return Some(distance);
+        }
+        let neighbours = [
+            (coord.x + 1, coord.y),
+            (coord.x, coord.y + 1),
+            (coord.x.wrapping_sub(1), coord.y),
+            (coord.x, coord.y.wrapping_sub(1)),
+        ];
+        for &(x, y) in &neighbours {
+            let free = rows
+                .get(y)
+                .and_then(|row| row.get(x))
+                .is_some_and(|&square| square != b'#');
+            if free && distances[y][x].is_none() {
+                distances[y][x] = Some(distance + 1);
+                queue.push_back(UCoord2::new(x, y));
+            }
+        }
+    }
+
+    None
+}
+
+fn is_adjacent(a: UCoord2, b: UCoord2) -> bool {
+    let v = b - a;
+    v.x.abs() + v.y.abs() == 1
+}
+
+#[test]
+fn compute_path_is_a_shortest_walkable_path() {
+    let mut a_star_algo = AStarAlgo::new();
+
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let labyrinth = random_labyrinth(&mut rng);
+        let board = Board::from_rows(&labyrinth.rows_as_strings(), labyrinth.rounds);
+        let squares = free_squares(&board);
+
+        for _ in 0..10 {
+            let start = *squares.choose(&mut rng).unwrap();
+            let target = *squares.choose(&mut rng).unwrap();
+            if start == target {
+                continue;
+            }
+
+            let path = a_star_algo.compute_path(&board, start, target, &[Content::Empty]);
+
+            assert!(
+                is_adjacent(start, path[0]),
+                "seed {}: {:?} -> {:?}",
+                seed,
+                start,
+                path
+            );
+            assert_eq!(*path.last().unwrap(), target, "seed {}", seed);
+            for step in path.windows(2) {
+                assert!(is_adjacent(step[0], step[1]), "seed {}: {:?}", seed, path);
+            }
+            for coord in &path {
+                assert_eq!(board.get_content(coord), Content::Empty, "seed {}", seed);
+            }
+            assert_eq!(
+                Some(path.len()),
+                bfs_distance(&labyrinth.rows, start, target),
+                "seed {}: {} -> {}",
+                seed,
+                start,
+                target
+            );
+        }
+    }
+}
+
+#[test]
+fn compute_path_accepts_unknown_squares_when_asked() {
+    let mut a_star_algo = AStarAlgo::new();
+
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let labyrinth = random_labyrinth(&mut rng);
+        let rows = labyrinth.rows_as_strings();
+
+        // Kirk only saw around the starting point, the rest is unknown.
+        let mut board = Board::new(labyrinth.width(), labyrinth.height(), labyrinth.rounds);
+        board.update_with(labyrinth.start, &rows);
+
+        let path = a_star_algo.compute_path(
+            &board,
+            labyrinth.start,
+            labyrinth.cmd_room,
+            &[Content::Empty, Content::Unknown],
+        );
+
+        assert_eq!(*path.last().unwrap(), labyrinth.cmd_room, "seed {}", seed);
+        for coord in &path {
+            assert_ne!(board.get_content(coord), Content::Wall, "seed {}", seed);
+        }
+        // Unknown squares may only make the path shorter than the real one.
+        assert!(
+            path.len()
+                <= bfs_distance(&labyrinth.rows, labyrinth.start, labyrinth.cmd_room).unwrap()
+        );
+    }
+}
+
+// Dijkstra from target, where entering a square costs its weight.
+fn weighted_distances(
+    board: &Board,
+    weights: &HashMap<UCoord2, i32>,
+    target: UCoord2,
+) -> HashMap<UCoord2, i32> {
+    let mut distances = HashMap::new();
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((0, target.x, target.y)));
+
+    while let Some(Reverse((distance, x, y))) = heap.pop() {
+        let coord = UCoord2::new(x, y);
+        if distances.contains_key(&coord) {
+            continue;
+        }
+        distances.insert(coord, distance);
+
+        for neighbour in board.neighbours_in_board_iter(coord, Direction::Left) {
+            if board.get_content(&neighbour) == Content::Empty
+                && !distances.contains_key(&neighbour)
+            {
+                // moving from neighbour to coord costs the weight of coord
+                heap.push(Reverse((
+                    distance + weights[&coord],
+                    neighbour.x,
+                    neighbour.y,
+                )));
+            }
+        }
+    }
+
+    distances
+}
+
+// Weighted A* from start, entering a square costs its weight and the heuristic of AStarAlgo is inflated by
+// 1 + epsilon, epsilon given as a fraction. Cost of the path found to target.
+fn weighted_a_star_cost(
+    a_star_algo: &AStarAlgo,
+    board: &Board,
+    weights: &HashMap<UCoord2, i32>,
+    start: UCoord2,
+    target: UCoord2,
+    (epsilon_num, epsilon_den): (i32, i32),
+) -> Option<i32> {
+    let priority = |cost: i32, coord: UCoord2| {
+        epsilon_den * cost + (epsilon_den + epsilon_num) * a_star_algo.heuristic(coord, target)
+    };
+    let mut costs = HashMap::new();
+    costs.insert(start, 0);
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((priority(0, start), 0, start.x, start.y)));
+
+    while let Some(Reverse((_, cost, x, y))) = heap.pop() {
+        let coord = UCoord2::new(x, y);
+        if coord == target {
+            return Some(cost);
+        }
+        if costs[&coord] < cost {
+            continue;
+        }
+        for neighbour in board.neighbours_in_board_iter(coord, Direction::Left) {
+            if board.get_content(&neighbour) != Content::Empty {
+                continue;
+            }
+            let neighbour_cost = cost + weights[&neighbour];
+            if costs
+                .get(&neighbour)
+                .is_none_or(|&known| neighbour_cost < known)
+            {
+                costs.insert(neighbour, neighbour_cost);
+                heap.push(Reverse((
+                    priority(neighbour_cost, neighbour),
+                    neighbour_cost,
+                    neighbour.x,
+                    neighbour.y,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+#[test]
+fn heuristic_stays_admissible_with_weighted_costs() {
+    let a_star_algo = AStarAlgo::new();
+
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let labyrinth = random_labyrinth(&mut rng);
+        let board = Board::from_rows(&labyrinth.rows_as_strings(), labyrinth.rounds);
+        let squares = free_squares(&board);
+
+        let weights: HashMap<_, _> = squares
+            .iter()
+            .map(|&coord| (coord, rng.gen_range(1, 10)))
+            .collect();
+        let target = *squares.choose(&mut rng).unwrap();
+        let distances = weighted_distances(&board, &weights, target);
+
+        // the path found with the heuristic inflated by 1 + epsilon costs at most 1 + epsilon times the optimal one,
+        // exactly the optimal one without inflation
+        let start = *squares.choose(&mut rng).unwrap();
+        for &(epsilon_num, epsilon_den) in &[(0, 1), (1, 2), (1, 1)] {
+            let cost = weighted_a_star_cost(
+                &a_star_algo,
+                &board,
+                &weights,
+                start,
+                target,
+                (epsilon_num, epsilon_den),
+            )
+            .unwrap();
+            let optimal = distances[&start];
+            assert!(
+                cost * epsilon_den <= (epsilon_den + epsilon_num) * optimal,
+                "seed {}: cost {} with epsilon {}/{}, optimal {}",
+                seed,
+                cost,
+                epsilon_num,
+                epsilon_den,
+                optimal
+            );
+            if epsilon_num == 0 {
+                assert_eq!(cost, optimal, "seed {}", seed);
+            }
+        }
+
+        for (coord, distance) in distances {
+            let heuristic = a_star_algo.heuristic(coord, target);
+            assert!(
+                heuristic <= distance,
+                "seed {}: heuristic {} > cost {} from {} to {}",
+                seed,
+                heuristic,
+                distance,
+                coord,
+                target
+            );
+        }
+    }
+}
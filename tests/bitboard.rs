@@ -0,0 +1,217 @@
+// Bitboards of the board knowledge against the per-square answers, on seeded labyrinths.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use the_labyrinth::bitboard::{Bitboard, BoardBits};
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::exploration;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::generator::Labyrinth;
+use the_labyrinth::protocol;
+use the_labyrinth::solver::Solver;
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+const SEEDS: u64 = 30;
+
+// A labyrinth wider than a word, with a part of its squares still unknown.
+fn partly_known_board(rng: &mut StdRng) -> Board {
+    let width = rng.gen_range(60, 150);
+    let height = rng.gen_range(5, 30);
+    let loop_ratio = rng.gen_range(0., 0.5);
+    let labyrinth = Labyrinth::generate(rng, width, height, loop_ratio);
+    let unknown_ratio = rng.gen_range(0., 0.6);
+    let rows: Vec<String> = labyrinth
+        .rows_as_strings()
+        .iter()
+        .map(|row| {
+            row.chars()
+                .map(|square| {
+                    if rng.gen_bool(unknown_ratio) {
+                        '?'
+                    } else {
+                        square
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Board::from_rows(&rows, 0)
+}
+
+#[test]
+fn shifts_cross_the_words_of_a_row() {
+    let mut bitboard = Bitboard::new(130, 3);
+    bitboard.set(UCoord2::new(63, 1), true);
+    bitboard.set(UCoord2::new(128, 0), true);
+
+    let right = bitboard.shifted(UDelta2::new(1, 0));
+    assert_eq!(
+        right.coords().collect::<Vec<_>>(),
+        vec![UCoord2::new(129, 0), UCoord2::new(64, 1)]
+    );
+    let down_left = bitboard.shifted(UDelta2::new(-65, 1));
+    assert_eq!(
+        down_left.coords().collect::<Vec<_>>(),
+        vec![UCoord2::new(63, 1)]
+    );
+    // (128, 0) leaves the board on the right
+    let right = bitboard.shifted(UDelta2::new(2, 0));
+    assert_eq!(
+        right.coords().collect::<Vec<_>>(),
+        vec![UCoord2::new(65, 1)]
+    );
+
+    assert_eq!(bitboard.dilated_orthogonal().count(), 5 + 4);
+}
+
+#[test]
+fn window_checks_mask_the_words() {
+    let mut bitboard = Bitboard::new(200, 4);
+    bitboard.set(UCoord2::new(130, 2), true);
+
+    assert!(bitboard.any_in(128..133, 0..3));
+    assert!(bitboard.any_in(0..200, 2..3));
+    assert!(!bitboard.any_in(131..200, 0..4));
+    assert!(!bitboard.any_in(0..130, 0..4));
+    assert!(!bitboard.any_in(128..133, 3..4));
+
+    let mut bits = BoardBits::new(200, 4);
+    for x in 0..200 {
+        for y in 0..4 {
+            bits.set(UCoord2::new(x, y), Content::Empty);
+        }
+    }
+    bits.set(UCoord2::new(130, 2), Content::Unknown);
+    assert!(bits.any_unknown_in_window(UCoord2::new(128, 0)));
+    assert!(!bits.any_unknown_in_window(UCoord2::new(127, 0)));
+    assert_eq!(bits.revealing_squares().count(), 5 * 4 - 1);
+}
+
+#[test]
+fn flood_fill_reaches_the_walkable_squares() {
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = partly_known_board(&mut rng);
+        board.set_bitboard(true);
+        let bits = board.bits().unwrap();
+
+        let start = bits.free().coords().next().unwrap();
+        let distances = exploration::walking_distances(&board, start);
+        let expected: Vec<_> = distances
+            .iter()
+            .filter(|(_, distance)| distance.is_some())
+            .map(|(coord, _)| coord)
+            .collect();
+        assert_eq!(
+            bits.reachable(start).coords().collect::<Vec<_>>(),
+            expected,
+            "seed {}",
+            seed
+        );
+    }
+}
+
+#[test]
+fn frontier_matches_the_squares() {
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = partly_known_board(&mut rng);
+
+        board.set_bitboard(false);
+        let frontier = exploration::frontier_squares(&board);
+        board.set_bitboard(true);
+        assert!(board.exact_bits().is_some());
+        assert_eq!(
+            exploration::frontier_squares(&board),
+            frontier,
+            "seed {}",
+            seed
+        );
+    }
+}
+
+#[test]
+fn radar_window_checks_match_the_squares() {
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = partly_known_board(&mut rng);
+        let coords: Vec<_> = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| UCoord2::new(x, y)))
+            .collect();
+        let reveals = |board: &Board| -> Vec<bool> {
+            coords
+                .iter()
+                .map(|&coord| exploration::reveals_unknown(board, coord))
+                .collect()
+        };
+
+        board.set_bitboard(false);
+        let revealing = exploration::revealing_squares(&board);
+        let revealed = reveals(&board);
+        board.set_bitboard(true);
+        assert!(board.exact_bits().is_some());
+        assert_eq!(
+            exploration::revealing_squares(&board),
+            revealing,
+            "seed {}",
+            seed
+        );
+        assert_eq!(reveals(&board), revealed, "seed {}", seed);
+    }
+}
+
+#[test]
+fn bitboards_are_kept_only_when_turned_on() {
+    assert!(Board::new(200, 100, 0).bits().is_none());
+
+    let mut board = Board::new(70, 70, 0);
+    board.set_bitboard(true);
+    let rows: Vec<String> = (0..70).map(|_| ".".repeat(70)).collect();
+    board.update_with(UCoord2::new(10, 10), &rows);
+    let bits = board.bits().unwrap();
+    // the radar window
+    assert_eq!(bits.free().count(), 25);
+    assert_eq!(bits.unknown().count(), 70 * 70 - 25);
+    assert!(bits.free().coords().all(|coord| (8..13).contains(&coord.x)));
+}
+
+// Moves of a whole game of the solver, through the GameSample protocol.
+fn played_moves(labyrinth: &Labyrinth, bitboard: bool) -> (Option<Outcome>, Vec<String>) {
+    let mut game_sample = GameSample::from_sample_string(&labyrinth.to_sample_string()).unwrap();
+    let mut solver = Solver::new(labyrinth.width(), labyrinth.height(), labyrinth.rounds);
+    solver.set_bitboard(bitboard);
+    let mut moves = Vec::new();
+
+    while game_sample.outcome().is_none() {
+        let data = game_sample.round_data();
+        let mut lines = data.lines();
+        let rick_coord = protocol::parse_turn_line(lines.next().unwrap()).unwrap();
+        let rows: Vec<String> = lines.map(str::to_owned).collect();
+        match solver.next_move(rick_coord, &rows) {
+            Some(dir) => {
+                moves.push(dir.label().to_owned());
+                game_sample.set_action(dir.label());
+            }
+            None => game_sample.stop(),
+        }
+    }
+
+    (game_sample.outcome(), moves)
+}
+
+#[test]
+fn games_are_the_same_with_bitboards() {
+    // generated labyrinths the solver wins
+    for &seed in &[1, 5] {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let labyrinth = Labyrinth::generate(&mut rng, 71, 59, 0.1);
+        let (outcome, moves) = played_moves(&labyrinth, true);
+        assert_eq!(
+            played_moves(&labyrinth, false),
+            (outcome, moves),
+            "seed {}",
+            seed
+        );
+        assert_eq!(outcome, Some(Outcome::Won), "seed {}", seed);
+    }
+}
@@ -0,0 +1,30 @@
+// The single file submission compiles with std only and plays like the crate.
+
+use std::{fs, path::Path, process::Command};
+
+use the_labyrinth::bundle;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::referee::{referee_process, TurnTimeouts};
+
+#[test]
+fn bundle_compiles_with_std_only_and_wins() {
+    let source = bundle::bundle(Path::new("src")).unwrap();
+    for excluded in &["game_sample", "itertools", "rand::", "mod render"] {
+        assert!(!source.contains(excluded), "{} is bundled", excluded);
+    }
+
+    let bundle_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("submission.rs");
+    fs::write(&bundle_path, source).unwrap();
+    let executable = bundle::verify(&bundle_path).unwrap();
+
+    for filename in &["samples/lab0.txt", "samples/lab8.txt"] {
+        let mut game_sample = GameSample::new(filename).unwrap();
+        let outcome = referee_process(
+            &mut game_sample,
+            &mut Command::new(&executable),
+            TurnTimeouts::default(),
+        )
+        .unwrap();
+        assert_eq!(outcome, Outcome::Won, "{}", filename);
+    }
+}
@@ -0,0 +1,64 @@
+// Coordinate arithmetic near the edges of maps without walls on their border.
+
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::vect2_t::{Bounds, UCoord2, UDelta2};
+
+#[test]
+fn checked_arithmetic_stops_at_zero() {
+    let origin = UCoord2::new(0, 2);
+    assert_eq!(origin.checked_add_delta(UDelta2::new(-1, 0)), None);
+    assert_eq!(origin.checked_add_delta(UDelta2::new(0, -3)), None);
+    assert_eq!(
+        origin.checked_add_delta(UDelta2::new(3, -2)),
+        Some(UCoord2::new(3, 0))
+    );
+
+    let bounds = Bounds::new(4, 3);
+    assert_eq!(origin.neighbour_in(UDelta2::new(0, 1), bounds), None);
+    assert_eq!(
+        origin.neighbour_in(UDelta2::new(3, 0), bounds),
+        Some(UCoord2::new(3, 2))
+    );
+    assert_eq!(origin.neighbour_in(UDelta2::new(4, 0), bounds), None);
+}
+
+#[test]
+#[should_panic(expected = "below zero")]
+fn unchecked_addition_below_zero_panics() {
+    let _ = UCoord2::new(0, 0) + UDelta2::new(0, -1);
+}
+
+#[test]
+fn bounds_list_their_squares() {
+    let bounds = Bounds::new(3, 2);
+    let coords: Vec<_> = bounds.coords().collect();
+    assert_eq!(coords.len(), 6);
+    assert_eq!(coords[4], UCoord2::new(1, 1));
+    assert!(coords.iter().all(|&coord| bounds.contains(coord)));
+    assert!(!bounds.contains(UCoord2::new(3, 0)));
+
+    let corner: Vec<_> = UCoord2::new(0, 0)
+        .neighbours_in(Direction::Left, bounds)
+        .collect();
+    assert_eq!(corner, vec![UCoord2::new(1, 0), UCoord2::new(0, 1)]);
+}
+
+#[test]
+fn games_without_border_walls() {
+    let map = "\
+T.....
+.####.
+.#..#.
+.#C.#.
+......";
+
+    let mut game_sample = GameSample::from_sample_string(&format!("0 0 20\n{}", map)).unwrap();
+    game_sample.play().unwrap();
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+
+    let mut game_sample = GameSample::from_sample_string(&format!("0 0 20\n{}", map)).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("LEFT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::HitWall));
+}
@@ -0,0 +1,59 @@
+// Labyrinths whose walls open and close during the game.
+
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::game_sample::{GameSample, Outcome, WallEvents};
+use the_labyrinth::vect2_t::UCoord2;
+
+const MAP: &str = "#############
+#.....#.....#
+#.T...#.....#
+#.....%...C.#
+#.....#.....#
+#############";
+
+fn rows(middle: &str) -> Vec<String> {
+    vec!["#####".to_owned(), middle.to_owned(), "#####".to_owned()]
+}
+
+#[test]
+fn board_records_last_seen_turns_and_changes() {
+    let mut board = Board::new(5, 3, 10);
+    board.update_with(UCoord2::new(1, 1), &rows("#K#.#"));
+    assert_eq!(board.last_seen(&UCoord2::new(3, 1)), Some(1));
+    assert!(board.changed_coords().is_empty());
+
+    board.update_with(UCoord2::new(1, 1), &rows("#K..#"));
+    assert_eq!(board.turn(), 2);
+    assert_eq!(board.last_seen(&UCoord2::new(3, 1)), Some(2));
+    assert_eq!(board.changed_coords(), &[UCoord2::new(2, 1)]);
+    assert_eq!(board.get_content(&UCoord2::new(2, 1)), Content::Empty);
+}
+
+#[test]
+fn scheduled_walls_toggle_and_show_as_plain_squares() {
+    let mut game_sample =
+        GameSample::from_sample_string("1 1 10 toggle=2\n######\n#.._%#\n######").unwrap();
+    assert_eq!(game_sample.wall_events(), WallEvents::Schedule(2));
+    assert!(game_sample.round_data().contains("#K..??"));
+
+    game_sample.set_action("RIGHT");
+    assert!(game_sample.round_data().contains("#.K.#"));
+    game_sample.set_action("RIGHT");
+    // the wall in front of Kirk opened, the one he stands on stays open
+    assert!(game_sample.round_data().contains("#..K."));
+    assert_eq!(game_sample.outcome(), None);
+}
+
+#[test]
+fn gate_is_crossed_whatever_the_schedule() {
+    for header in &[
+        "2 2 40 toggle=3",
+        "2 2 40 toggle=10",
+        "2 2 40 random=0.1 seed=4",
+    ] {
+        let mut game_sample =
+            GameSample::from_sample_string(&format!("{}\n{}", header, MAP)).unwrap();
+        game_sample.play().unwrap();
+        assert_eq!(game_sample.outcome(), Some(Outcome::Won), "{}", header);
+    }
+}
@@ -0,0 +1,70 @@
+// The flat grid behind the board, the referee and the distance maps.
+
+use the_labyrinth::board::Board;
+use the_labyrinth::exploration;
+use the_labyrinth::grid::Grid;
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+fn sample_grid() -> Grid<u8> {
+    Grid::from_rows(vec![b"#.#".to_vec(), b"...".to_vec(), b"C#T".to_vec()])
+}
+
+#[test]
+fn indexing_by_coordinates_and_rows() {
+    let mut grid = sample_grid();
+    assert_eq!((grid.width(), grid.height()), (3, 3));
+    assert_eq!(grid[UCoord2::new(2, 2)], b'T');
+    assert_eq!(&grid[1], b"...");
+    assert_eq!(grid.get(UCoord2::new(3, 0)), None);
+
+    grid[UCoord2::new(1, 1)] = b'K';
+    assert_eq!(grid[1][1], b'K');
+    assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), b".K#");
+    assert_eq!(grid.to_rows()[1], b".K.".to_vec());
+}
+
+#[test]
+fn windows_and_neighbours_stay_on_the_grid() {
+    let grid = sample_grid();
+    let window: Vec<_> = grid
+        .window(&[0, 1], &[1, 2])
+        .map(|(_, &square)| square)
+        .collect();
+    assert_eq!(window, b"..C#");
+
+    let deltas = [
+        UDelta2::new(-1, 0),
+        UDelta2::new(0, -1),
+        UDelta2::new(1, 0),
+        UDelta2::new(0, 1),
+    ];
+    let neighbours: Vec<_> = grid.neighbours(UCoord2::new(0, 0), &deltas).collect();
+    assert_eq!(
+        neighbours,
+        vec![(UCoord2::new(1, 0), &b'.'), (UCoord2::new(0, 1), &b'.')]
+    );
+}
+
+#[test]
+fn map_and_zip_keep_the_size() {
+    let grid = sample_grid();
+    let walls = grid.map(|&square| square == b'#');
+    assert_eq!(walls.iter().filter(|&(_, &wall)| wall).count(), 3);
+
+    let zipped = grid.zip(&walls);
+    assert_eq!(zipped[UCoord2::new(1, 2)], (&b'#', &true));
+    assert_eq!(zipped.width(), 3);
+}
+
+#[test]
+fn walking_distances_are_a_grid() {
+    let rows: Vec<String> = ["#####", "#...#", "#.#.#", "#####"]
+        .iter()
+        .map(|row| row.to_string())
+        .collect();
+    let board = Board::from_rows(&rows, 0);
+    let distances = exploration::walking_distances(&board, UCoord2::new(1, 2));
+
+    assert_eq!(distances[UCoord2::new(3, 2)], Some(4));
+    assert_eq!(distances[UCoord2::new(2, 2)], None);
+}
@@ -0,0 +1,122 @@
+// Hazards and patrolling guards, for the planners and the referee.
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::guards::{Forecast, Guard};
+use the_labyrinth::vect2_t::UCoord2;
+
+#[test]
+fn guard_turns_back_at_the_end_of_its_corridor() {
+    let is_open = |coord: UCoord2| coord.y == 1 && (1..=3).contains(&coord.x);
+    let guard = Guard::from_byte(UCoord2::new(2, 1), b'>').unwrap();
+
+    let guard = guard.step(is_open);
+    assert_eq!(guard, Guard::new(UCoord2::new(3, 1), Direction::Right));
+    let guard = guard.step(is_open);
+    assert_eq!(guard, Guard::new(UCoord2::new(2, 1), Direction::Left));
+    assert_eq!(guard.byte(), b'<');
+}
+
+#[test]
+fn forecast_forbids_meeting_and_crossing_a_guard() {
+    let guard = Guard::new(UCoord2::new(3, 1), Direction::Left);
+    let forecast = Forecast::new(&[guard], 4, |coord| {
+        coord.y == 1 && (1..=5).contains(&coord.x)
+    });
+
+    assert_eq!(forecast.turns(), 4);
+    // the guard walks to (2, 1) at the first turn, Kirk may not step where it was
+    assert!(!forecast.is_safe(UCoord2::new(3, 1), 1));
+    assert!(!forecast.is_safe(UCoord2::new(2, 1), 1));
+    assert!(forecast.is_safe(UCoord2::new(4, 1), 1));
+    assert!(forecast.is_safe(UCoord2::new(2, 1), 5));
+}
+
+#[test]
+fn board_sees_hazards_and_guards() {
+    let mut board = Board::new(5, 3, 10);
+    let rows = |row: &str| vec!["#####".to_owned(), row.to_owned(), "#####".to_owned()];
+
+    board.update_with(UCoord2::new(1, 1), &rows("#K~v#"));
+    assert_eq!(board.get_content(&UCoord2::new(2, 1)), Content::Hazard);
+    assert_eq!(board.get_content(&UCoord2::new(3, 1)), Content::Empty);
+    assert_eq!(
+        board.guards(),
+        &[Guard::new(UCoord2::new(3, 1), Direction::Down)]
+    );
+
+    board.update_with(UCoord2::new(1, 1), &rows("#K~.#"));
+    assert!(board.guards().is_empty());
+}
+
+#[test]
+fn space_time_path_steps_aside_until_the_guard_is_gone() {
+    let rows: Vec<String> = ["#####", "#...#", "#.#.#", "#...#", "#####"]
+        .iter()
+        .map(|row| row.to_string())
+        .collect();
+    let board = Board::from_rows(&rows, 0);
+    let guarded = UCoord2::new(2, 1);
+
+    let path = AStarAlgo::new().compute_path_in_time(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(3, 1),
+        &[Content::Empty],
+        4,
+        |coord, turn| coord != guarded || turn > 2,
+    );
+    assert_eq!(
+        path,
+        vec![
+            UCoord2::new(1, 2),
+            UCoord2::new(1, 1),
+            guarded,
+            UCoord2::new(3, 1)
+        ]
+    );
+}
+
+#[test]
+fn stepping_on_a_hazard_or_into_a_guard_ends_the_game() {
+    let mut game_sample = GameSample::from_sample_string("1 1 10\n#####\n#.~.#\n#####").unwrap();
+    game_sample.round_data();
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::Hazard));
+
+    let mut game_sample = GameSample::from_sample_string("1 1 10\n######\n#..<.#\n######").unwrap();
+    assert!(game_sample.round_data().contains("#K.<??"));
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::Caught));
+}
+
+#[test]
+fn winning_on_the_square_of_a_guard_is_still_a_win() {
+    // the guard comes up on the starting point while Kirk visits the control room
+    let mut game_sample = GameSample::from_sample_string("1 1 10\n####\n#.C#\n#^##\n####").unwrap();
+    game_sample.round_data();
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), None);
+    game_sample.round_data();
+    game_sample.set_action("LEFT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+}
+
+#[test]
+fn view_forgets_guards_out_of_the_radar_window() {
+    let mut game_sample = GameSample::from_sample_string(
+        "3 1 20\n##########\n#........#\n##########\n#..<.....#\n##########",
+    )
+    .unwrap();
+    let row_3 = |round_data: String| round_data.lines().nth(4).unwrap().to_owned();
+
+    assert_eq!(row_3(game_sample.round_data()), "?..<..????");
+    game_sample.set_action("RIGHT");
+    assert_eq!(row_3(game_sample.round_data()), "?.<....???");
+    // the guard walked out of the window, the bot keeps the empty square it saw under it
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.guards()[0].coord(), UCoord2::new(1, 3));
+    assert_eq!(row_3(game_sample.round_data()), "?.......??");
+}
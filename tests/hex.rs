@@ -0,0 +1,128 @@
+// Hexagonal grid, odd rows shifted half a cell to the right.
+
+use std::sync::Arc;
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::topology::{HexGrid, Topology};
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+#[test]
+fn neighbours_depend_on_the_row_parity() {
+    let hex = HexGrid::new();
+    let neighbours = |coord: UCoord2| -> Vec<UCoord2> {
+        hex.deltas(coord)
+            .iter()
+            .map(|&delta| coord + delta)
+            .collect()
+    };
+
+    assert_eq!(
+        neighbours(UCoord2::new(2, 2)),
+        [(1, 2), (1, 1), (2, 1), (3, 2), (2, 3), (1, 3)]
+            .iter()
+            .map(|&(x, y)| UCoord2::new(x, y))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        neighbours(UCoord2::new(2, 3)),
+        [(1, 3), (2, 2), (3, 2), (3, 3), (3, 4), (2, 4)]
+            .iter()
+            .map(|&(x, y)| UCoord2::new(x, y))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn distance_counts_hex_moves() {
+    let hex = HexGrid::new();
+    let origin = UCoord2::new(3, 3);
+    for &delta in hex.deltas(origin) {
+        assert_eq!(hex.distance(origin, origin + delta), 1);
+    }
+    // two rows down the shift gives a free half cell each row
+    assert_eq!(hex.distance(UCoord2::new(0, 0), UCoord2::new(1, 2)), 2);
+    assert_eq!(hex.distance(UCoord2::new(0, 0), UCoord2::new(3, 0)), 3);
+    assert_eq!(hex.distance(UCoord2::new(4, 5), UCoord2::new(0, 0)), 7);
+}
+
+#[test]
+fn labels_go_both_ways() {
+    let hex = HexGrid::new();
+    for coord in [UCoord2::new(2, 2), UCoord2::new(2, 3)] {
+        for &delta in hex.deltas(coord) {
+            assert_eq!(
+                hex.parse_label(coord, &hex.label(coord, delta)),
+                Some(delta)
+            );
+        }
+    }
+    assert_eq!(
+        hex.parse_label(UCoord2::new(2, 2), "NE"),
+        Some(UDelta2::new(0, -1))
+    );
+    assert_eq!(
+        hex.parse_label(UCoord2::new(2, 3), "NE"),
+        Some(UDelta2::new(1, -1))
+    );
+    assert_eq!(hex.parse_label(UCoord2::new(2, 3), "UP"), None);
+}
+
+#[test]
+fn paths_follow_the_hexagons() {
+    let rows: Vec<String> = ["#####", "#...#", "#...#", "#...#", "#####"]
+        .iter()
+        .map(|row| row.to_string())
+        .collect();
+    let mut board = Board::from_rows(&rows, 0);
+    board.set_topology(Arc::new(HexGrid::new()));
+
+    // (1, 1) and (2, 2) are next to each other on an odd row
+    assert!(board
+        .neighbours_in_board_iter(UCoord2::new(1, 1), Direction::Left)
+        .any(|coord| coord == UCoord2::new(2, 2)));
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(3, 3),
+        &[Content::Empty],
+    );
+    assert_eq!(path.len(), 3);
+}
+
+#[test]
+fn referee_reads_spaced_hex_rows() {
+    let sample = "1 1 10 grid=hex\n# # # #\n # . . #\n# . # #\n # # # #";
+
+    let mut game_sample = GameSample::from_sample_string(sample).unwrap();
+    for (action, coord) in [
+        ("SW", UCoord2::new(1, 2)),
+        ("NE", UCoord2::new(1, 1)),
+        ("E", UCoord2::new(2, 1)),
+    ] {
+        game_sample.round_data();
+        game_sample.set_action(action);
+        assert_eq!(game_sample.outcome(), None);
+        assert_eq!(game_sample.current(), coord);
+    }
+    game_sample.round_data();
+    game_sample.set_action("LEFT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::InvalidAction));
+
+    // below (2, 1) on the right is a wall
+    let mut game_sample = GameSample::from_sample_string(sample).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("E");
+    game_sample.round_data();
+    game_sample.set_action("SE");
+    assert_eq!(game_sample.outcome(), Some(Outcome::HitWall));
+}
+
+#[test]
+fn hex_sample_is_won() {
+    let mut game_sample = GameSample::new("samples/lab_hex.txt").unwrap();
+    game_sample.play().unwrap();
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+}
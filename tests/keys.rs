@@ -0,0 +1,67 @@
+// Coloured doors and their keys, for the planners and the referee.
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::keys::{KeyColor, KeySet};
+use the_labyrinth::vect2_t::UCoord2;
+
+fn board(rows: &[&str]) -> Board {
+    let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+    Board::from_rows(&rows, 0)
+}
+
+#[test]
+fn path_goes_through_the_key_before_the_door() {
+    let board = board(&["#######", "#.R...#", "#.###.#", "#..r#.#", "#######"]);
+    let door = UCoord2::new(2, 1);
+    let key = UCoord2::new(3, 3);
+
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(3, 1),
+        &[Content::Empty],
+    );
+
+    let key_step = path.iter().position(|&coord| coord == key).unwrap();
+    let door_step = path.iter().position(|&coord| coord == door).unwrap();
+    assert!(key_step < door_step, "{:?}", path);
+    assert_eq!(path.last(), Some(&UCoord2::new(3, 1)));
+}
+
+#[test]
+fn door_without_its_key_is_a_wall() {
+    let board = board(&["#######", "#.G...#", "#######"]);
+    assert_eq!(
+        board.get_content(&UCoord2::new(2, 1)),
+        Content::Door(KeyColor::Green)
+    );
+
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(5, 1),
+        &[Content::Empty],
+    );
+    assert!(path.is_empty(), "{:?}", path);
+}
+
+#[test]
+fn key_is_picked_up_by_walking_on_it() {
+    let mut game_sample = GameSample::from_sample_string("1 1 10\n#####\n#.bB.\n#####").unwrap();
+
+    game_sample.set_action("RIGHT");
+    assert!(game_sample.keys().contains(KeyColor::Blue));
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), None);
+}
+
+#[test]
+fn crossing_a_door_without_its_key_loses() {
+    let mut game_sample = GameSample::from_sample_string("1 1 10\n#####\n#.Yy.\n#####").unwrap();
+
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::LockedDoor));
+    assert_eq!(game_sample.keys(), KeySet::new());
+}
@@ -0,0 +1,136 @@
+// Movement models other than the four CodinGame moves.
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::movement::{self, Movement};
+use the_labyrinth::stats::GameStats;
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+fn board(rows: &[&str], movement: Movement) -> Board {
+    let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+    let mut board = Board::from_rows(&rows, 0);
+    board.set_movement(movement);
+    board
+}
+
+#[test]
+fn labels_go_both_ways() {
+    let movements = [
+        Movement::orthogonal(),
+        Movement::eight_directions(false),
+        Movement::knight(),
+    ];
+    for delta in movements.iter().flat_map(Movement::deltas) {
+        assert_eq!(movement::from_label(&movement::label(*delta)), Some(*delta));
+    }
+
+    assert_eq!(movement::label(UDelta2::new(0, -1)), "UP");
+    assert_eq!(movement::label(UDelta2::new(-1, 1)), "DOWN_LEFT");
+    assert_eq!(movement::label(UDelta2::new(2, -1)), "MOVE 2 -1");
+    assert_eq!(movement::from_label("LEFT_UP"), None);
+    assert_eq!(
+        Movement::parse("1:0,0:2"),
+        Some(Movement::custom(vec![
+            UDelta2::new(1, 0),
+            UDelta2::new(0, 2)
+        ]))
+    );
+    assert_eq!(Movement::parse("sideways"), None);
+}
+
+#[test]
+fn heuristic_matches_the_movement_model() {
+    let delta = UDelta2::new(-4, 3);
+    assert_eq!(Movement::orthogonal().heuristic(delta), 7);
+    assert_eq!(Movement::eight_directions(false).heuristic(delta), 4);
+    // a knight jump covers at most 3 squares, 2 on one axis
+    assert_eq!(Movement::knight().heuristic(delta), 3);
+}
+
+#[test]
+fn diagonal_moves_cut_corners_only_when_allowed() {
+    let rows = ["#####", "#...#", "#.#.#", "#####"];
+    let is_next = |board: &Board, from: UCoord2, to: UCoord2| {
+        board
+            .neighbours_in_board_iter(from, Direction::Left)
+            .any(|coord| coord == to)
+    };
+
+    let strict = board(&rows, Movement::eight_directions(false));
+    assert!(!is_next(&strict, UCoord2::new(2, 1), UCoord2::new(3, 2)));
+    let cutting = board(&rows, Movement::eight_directions(true));
+    assert!(is_next(&cutting, UCoord2::new(2, 1), UCoord2::new(3, 2)));
+
+    // never between two walls
+    let cutting = board(
+        &["####", "#.##", "##.#", "####"],
+        Movement::eight_directions(true),
+    );
+    assert!(!is_next(&cutting, UCoord2::new(1, 1), UCoord2::new(2, 2)));
+}
+
+#[test]
+fn eight_directions_paths_are_diagonal() {
+    let rows = ["#######", "#.....#", "#.....#", "#.....#", "#######"];
+    let board = board(&rows, Movement::eight_directions(false));
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(5, 3),
+        &[Content::Empty],
+    );
+
+    assert_eq!(path.len(), 4);
+    assert_eq!(path.last(), Some(&UCoord2::new(5, 3)));
+    assert_eq!(
+        board.step_to(UCoord2::new(1, 1), UCoord2::new(2, 2)),
+        UDelta2::new(1, 1)
+    );
+}
+
+#[test]
+fn referee_checks_the_moves_of_the_model() {
+    let map = "#####\n#...#\n#.#.#\n#####";
+
+    let mut game_sample = GameSample::from_sample_string(&format!("2 1 10\n{}", map)).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("DOWN_RIGHT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::InvalidAction));
+
+    let mut game_sample =
+        GameSample::from_sample_string(&format!("2 1 10 moves=8\n{}", map)).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("DOWN_RIGHT");
+    assert_eq!(game_sample.outcome(), Some(Outcome::HitWall));
+
+    let mut game_sample =
+        GameSample::from_sample_string(&format!("2 1 10 moves=8cut\n{}", map)).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("DOWN_RIGHT");
+    assert_eq!(game_sample.outcome(), None);
+    assert_eq!(game_sample.current(), UCoord2::new(3, 2));
+}
+
+#[test]
+fn diagonal_sample_is_played_with_diagonal_moves() {
+    let mut game_sample = GameSample::new(&format!(
+        "{}/samples/lab_diagonal.txt",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    game_sample.play().unwrap();
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+
+    let replay = game_sample.replay();
+    let diagonal_moves = replay
+        .trajectory()
+        .windows(2)
+        .filter(|step| step[0].x != step[1].x && step[0].y != step[1].y)
+        .count();
+    assert!(diagonal_moves > 0);
+    // the return through the open room is as short as the diagonal moves make it
+    let stats = GameStats::from_replay(replay);
+    assert_eq!(stats.return_length, stats.optimal_return_length);
+}
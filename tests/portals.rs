@@ -0,0 +1,109 @@
+// Portal pairs, for the planners and the referee.
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::GameSample;
+use the_labyrinth::grid::Grid;
+use the_labyrinth::keys::KeySet;
+use the_labyrinth::movement::Movement;
+use the_labyrinth::stats;
+use the_labyrinth::vect2_t::UCoord2;
+
+const ROWS: &[&str] = &[
+    "##########",
+    "#.1#....1#",
+    "#..#.##..#",
+    "#........#",
+    "##########",
+];
+
+fn board() -> Board {
+    let rows: Vec<String> = ROWS.iter().map(|row| row.to_string()).collect();
+    Board::from_rows(&rows, 0)
+}
+
+#[test]
+fn path_goes_through_the_portal() {
+    let board = board();
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(7, 1),
+        &[Content::Empty],
+    );
+
+    assert_eq!(path, vec![UCoord2::new(8, 1), UCoord2::new(7, 1)]);
+    assert_eq!(
+        board.direction_to(UCoord2::new(1, 1), UCoord2::new(8, 1)),
+        Direction::Right
+    );
+}
+
+#[test]
+fn heuristic_never_overestimates_with_portals() {
+    let board = board();
+    let map = Grid::from_rows(ROWS.iter().map(|row| row.as_bytes().to_vec()).collect());
+    let target = UCoord2::new(4, 1);
+    let mut a_star_algo = AStarAlgo::new();
+    a_star_algo.compute_path(&board, UCoord2::new(1, 1), target, &[Content::Empty]);
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let coord = UCoord2::new(x, y);
+            if let Some(distance) = stats::shortest_distance(
+                &map,
+                &Movement::orthogonal(),
+                coord,
+                KeySet::new(),
+                target,
+            ) {
+                assert!(
+                    a_star_algo.heuristic(coord, target) as usize <= distance,
+                    "{:?}",
+                    coord
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn stepping_on_a_portal_moves_kirk_to_its_pair() {
+    let mut game_sample =
+        GameSample::from_sample_string("1 1 10\n#######\n#.2..2#\n#######").unwrap();
+
+    game_sample.round_data();
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.outcome(), None);
+    assert_eq!(
+        game_sample.replay().trajectory(),
+        vec![UCoord2::new(1, 1), UCoord2::new(5, 1)]
+    );
+    assert!(game_sample.round_data().starts_with("1 5\n"));
+}
+
+#[test]
+fn portal_exit_under_kirk_is_discovered() {
+    let mut board = Board::new(7, 3, 10);
+    let rows = |row: &str| vec!["#######".to_owned(), row.to_owned(), "#######".to_owned()];
+
+    board.update_with(UCoord2::new(1, 1), &rows("#K2??.#"));
+    board.update_with(UCoord2::new(5, 1), &rows("#.2?.K#"));
+
+    assert_eq!(board.get_content(&UCoord2::new(5, 1)), Content::Portal(2));
+    assert_eq!(
+        board.portal_exit(UCoord2::new(2, 1)),
+        Some(UCoord2::new(5, 1))
+    );
+}
+
+#[test]
+fn view_forgets_kirk_before_a_portal_jump() {
+    let mut game_sample =
+        GameSample::from_sample_string("1 1 10\n###########\n#.1.....1.#\n###########").unwrap();
+    game_sample.round_data();
+    game_sample.set_action("RIGHT");
+    assert_eq!(game_sample.current(), UCoord2::new(8, 1));
+    assert_eq!(game_sample.round_data().lines().nth(2), Some("#.1.??..K.#"));
+}
@@ -0,0 +1,210 @@
+// The bot side of the CodinGame protocol on well formed and broken inputs.
+
+use std::process::Command;
+
+use the_labyrinth::fuzz::{self, Target};
+use the_labyrinth::game_sample::{GameSample, Outcome, SampleError};
+use the_labyrinth::protocol::{self, ProtocolError};
+
+fn play(input: &str) -> (Result<(), ProtocolError>, String) {
+    let mut output = Vec::new();
+    let result = protocol::play(input.as_bytes(), &mut output);
+    (result, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn closed_input_between_turns_ends_the_game() {
+    let (result, output) = play("3 4 1\n1 1\n####\n#TC#\n####\n");
+    assert_eq!(result, Ok(()));
+    assert_eq!(output, "RIGHT\n");
+}
+
+#[test]
+fn empty_input_is_eof() {
+    assert_eq!(play("").0, Err(ProtocolError::Eof));
+}
+
+#[test]
+fn malformed_headers_are_reported() {
+    for input in &["3 4\n", "3 4 x\n", "0 4 1\n", "3 4 1 2 5\n", "-3 4 1\n"] {
+        assert!(
+            matches!(play(input).0, Err(ProtocolError::MalformedHeader(_))),
+            "{:?}",
+            input
+        );
+    }
+    assert_eq!(
+        play("3 4 1\n1\n").0,
+        Err(ProtocolError::MalformedHeader("1".to_owned()))
+    );
+}
+
+#[test]
+fn wrong_row_count_is_reported() {
+    assert_eq!(
+        play("3 4 1\n1 1\n####\n#TC#\n").0,
+        Err(ProtocolError::WrongRowCount {
+            expected: 3,
+            actual: 2
+        })
+    );
+}
+
+#[test]
+fn wrong_row_width_is_reported() {
+    assert_eq!(
+        play("3 4 1\n1 1\n####\n#TC\n####\n").0,
+        Err(ProtocolError::WrongRowWidth {
+            row: 1,
+            expected: 4,
+            actual: 3
+        })
+    );
+}
+
+#[test]
+fn kirk_out_of_bounds_is_reported() {
+    assert_eq!(
+        play("3 4 1\n3 1\n####\n#TC#\n####\n").0,
+        Err(ProtocolError::KirkOutOfBounds {
+            coord: (1, 3).into(),
+            width: 4,
+            height: 3
+        })
+    );
+}
+
+#[test]
+fn invalid_utf8_is_an_io_error() {
+    let mut output = Vec::new();
+    let result = protocol::play(&b"3 4 1\n1 1\n##\xff#\n"[..], &mut output);
+    assert!(matches!(result, Err(ProtocolError::Io(_))));
+}
+
+#[test]
+fn broken_labyrinth_files_are_errors() {
+    let error = |sample: &str| GameSample::from_sample_string(sample).err();
+
+    assert_eq!(
+        error("1 x 10\n###\n#.#\n###"),
+        Some(SampleError::MalformedHeader("1 x 10".to_owned()))
+    );
+    assert_eq!(
+        error("1 1 10 toggle=often\n###\n#.#\n###"),
+        Some(SampleError::MalformedHeader(
+            "1 1 10 toggle=often".to_owned()
+        ))
+    );
+    assert_eq!(error("1 1 10\n"), Some(SampleError::NoRows));
+    assert_eq!(
+        error("1 1 10\n###\n#.\n###"),
+        Some(SampleError::WrongRowWidth {
+            row: 1,
+            expected: 3,
+            actual: 2
+        })
+    );
+    // newlines after the last row are not rows, an empty row before the others is
+    assert!(error("1 1 10\n###\n#.#\n###\n\n\n").is_none());
+    assert_eq!(error("1 1 10\n\n\n"), Some(SampleError::NoRows));
+    assert_eq!(
+        error("1 1 10\n\n###\n#.#\n###"),
+        Some(SampleError::WrongRowWidth {
+            row: 0,
+            expected: 3,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        error("1 1 10\n###\n#.#\n#é#"),
+        Some(SampleError::NonAsciiSquare { row: 2, column: 1 })
+    );
+    assert!(matches!(
+        error("3 1 10\n###\n#.#\n###"),
+        Some(SampleError::StartOutOfBounds { .. })
+    ));
+    assert!(error("1 1 10\n###\n#.#\n###").is_none());
+}
+
+#[test]
+fn control_rooms_to_visit_are_on_the_map() {
+    let error = |sample: &str| GameSample::from_sample_string(sample).err();
+    let malformed = |header: &str| Some(SampleError::MalformedHeader(header.to_owned()));
+
+    assert_eq!(error("1 1 5 0\n####\n#.C#\n####"), malformed("1 1 5 0"));
+    assert_eq!(error("1 1 5 all\n####\n#..#\n####"), malformed("1 1 5 all"));
+    assert_eq!(error("1 1 5 2\n####\n#.C#\n####"), malformed("1 1 5 2"));
+
+    let mut game_sample = GameSample::from_sample_string("1 1 5 all\n####\n#.C#\n####").unwrap();
+    assert!(game_sample.play().is_ok());
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+}
+
+#[test]
+fn headers_without_rounds_get_the_way_back_and_a_margin() {
+    // the first labyrinth files: "x y" only and Kirk's K on the start
+    let game_sample = GameSample::from_sample_string(
+        "6 1\n#####################\n######K........C#####\n#####################",
+    )
+    .unwrap();
+    assert_eq!(game_sample.settings().rounds, 9 + 2);
+    assert_eq!(game_sample.replay().map()[1][6], b'T');
+
+    // no control room to come back from
+    assert_eq!(
+        GameSample::from_sample_string("1 1\n#####\n#...#\n#####").err(),
+        Some(SampleError::MalformedHeader("1 1".to_owned()))
+    );
+
+    for filename in &["samples/lab0.txt", "samples/lab0b.txt", "samples/lab0c.txt"] {
+        let mut game_sample = GameSample::new(filename).unwrap();
+        assert!(game_sample.play().is_ok());
+        assert_eq!(game_sample.outcome(), Some(Outcome::Won), "{}", filename);
+    }
+}
+
+#[test]
+fn fuzzed_targets_do_not_panic() {
+    for &target in Target::all().iter() {
+        let findings = fuzz::fuzz(target, 3000, 1);
+        assert!(
+            findings.is_empty(),
+            "{}: {:?}",
+            target.name(),
+            findings
+                .iter()
+                .map(|finding| format!("{} at {}", finding.message, finding.location))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn bad_numbers_of_fuzz_and_generate_are_usage_errors() {
+    for (args, message) in &[
+        (
+            vec!["fuzz", "protocol", "many"],
+            "Invalid iterations \"many\"",
+        ),
+        (vec!["fuzz", "protocol", "10", "-1"], "Invalid seed \"-1\""),
+        (
+            vec!["generate", "wide", "10", "0", "0.1"],
+            "Invalid width \"wide\"",
+        ),
+        (
+            vec!["generate", "10", "10", "0", "some"],
+            "Invalid loop_ratio \"some\"",
+        ),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_the_labyrinth"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains(message),
+            "{:?}",
+            args
+        );
+    }
+}
@@ -0,0 +1,118 @@
+// Games refereed over a local TCP socket or pipes to a process, with the line protocol of CodinGame.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::protocol;
+use the_labyrinth::referee::{referee, referee_process, TurnTimeouts};
+
+// Referee lab0 for one connection, the bot plays in the calling thread.
+fn play_over_tcp<F: FnOnce(TcpStream)>(bot: F) -> Outcome {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut game_sample = GameSample::new("samples/lab0.txt").unwrap();
+        referee(&mut game_sample, BufReader::new(&stream), &stream).unwrap()
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    stream.set_nodelay(true).unwrap();
+    bot(stream);
+    server.join().unwrap()
+}
+
+#[test]
+fn solver_wins_through_a_socket() {
+    let outcome = play_over_tcp(|stream| {
+        protocol::play(BufReader::new(&stream), &stream).unwrap();
+    });
+    assert_eq!(outcome, Outcome::Won);
+}
+
+#[test]
+fn unknown_action_loses() {
+    let outcome = play_over_tcp(|mut stream| {
+        let mut init_line = String::new();
+        BufReader::new(&stream).read_line(&mut init_line).unwrap();
+        assert_eq!(init_line.split_whitespace().count(), 3);
+        writeln!(stream, "JUMP").unwrap();
+    });
+    assert_eq!(outcome, Outcome::InvalidAction);
+}
+
+#[test]
+fn closed_connection_gives_up() {
+    let outcome = play_over_tcp(drop);
+    assert_eq!(outcome, Outcome::NoAction);
+}
+
+fn bot_binary() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_the_labyrinth"));
+    command.arg("stdin");
+    command
+}
+
+#[test]
+fn bot_process_wins_through_pipes() {
+    let mut game_sample = GameSample::new("samples/lab8.txt").unwrap();
+    let outcome =
+        referee_process(&mut game_sample, &mut bot_binary(), TurnTimeouts::default()).unwrap();
+    assert_eq!(outcome, Outcome::Won);
+}
+
+#[cfg(unix)]
+#[test]
+fn silent_bot_process_times_out() {
+    let timeouts = TurnTimeouts {
+        first_turn: Duration::from_millis(100),
+        turn: Duration::from_millis(100),
+    };
+    let mut game_sample = GameSample::new("samples/lab0.txt").unwrap();
+    let start = Instant::now();
+    let outcome = referee_process(
+        &mut game_sample,
+        Command::new("sh").args(["-c", "exec sleep 10"]),
+        timeouts,
+    )
+    .unwrap();
+    assert_eq!(outcome, Outcome::Timeout);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[test]
+fn exiting_bot_process_gives_up() {
+    let mut game_sample = GameSample::new("samples/lab0.txt").unwrap();
+    let outcome = referee_process(
+        &mut game_sample,
+        Command::new("sh").args(["-c", "exit 0"]),
+        TurnTimeouts::default(),
+    )
+    .unwrap();
+    assert_eq!(outcome, Outcome::NoAction);
+}
+
+#[test]
+fn invalid_turn_timeout_is_a_usage_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_the_labyrinth"))
+        .args([
+            "referee",
+            "samples/lab0.txt",
+            "--turn-timeout",
+            "fast",
+            "true",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid turn timeout \"fast\""));
+}
@@ -0,0 +1,274 @@
+// Pictures of a recorded game : the PNG frames are decoded back, the SVG pictures are checked to be well formed
+// and the text of the HTML page to be escaped.
+
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::render::{self, html, png, svg};
+use the_labyrinth::replay::Replay;
+use the_labyrinth::stats::GameStats;
+
+fn played(filename: &str) -> Replay {
+    let mut game_sample = GameSample::new(&format!(
+        "{}/samples/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        filename
+    ))
+    .unwrap();
+    game_sample.play().unwrap();
+    game_sample.replay().clone()
+}
+
+// Bitwise CRC of the PNG specification, written apart from the encoder's.
+fn crc32(data: &[u8]) -> u32 {
+    let table: Vec<u32> = (0..256u32)
+        .map(|n| {
+            (0..8).fold(n, |c, _| {
+                if c & 1 == 1 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                }
+            })
+        })
+        .collect();
+    !data.iter().fold(!0u32, |crc, &byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// Chunks of a PNG file, after checking the signature and the CRC of each chunk.
+fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n", "PNG signature");
+    let mut chunks = Vec::new();
+    let mut rest = &png[8..];
+    while !rest.is_empty() {
+        let len = be_u32(rest) as usize;
+        let kind_and_data = &rest[4..8 + len];
+        assert_eq!(
+            be_u32(&rest[8 + len..]),
+            crc32(kind_and_data),
+            "CRC of chunk {:?}",
+            &kind_and_data[..4]
+        );
+        let mut kind = [0; 4];
+        kind.copy_from_slice(&kind_and_data[..4]);
+        chunks.push((kind, kind_and_data[4..].to_vec()));
+        rest = &rest[12 + len..];
+    }
+    chunks
+}
+
+// Inflate a zlib stream made of stored deflate blocks, the only ones the encoder writes.
+fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        (u16::from(zlib[0]) << 8 | u16::from(zlib[1])) % 31,
+        0,
+        "zlib header check"
+    );
+    let mut data = Vec::new();
+    let mut pos = 2;
+    loop {
+        let header = zlib[pos];
+        assert_eq!(header >> 1, 0, "stored block");
+        let len = u16::from_le_bytes([zlib[pos + 1], zlib[pos + 2]]);
+        let nlen = u16::from_le_bytes([zlib[pos + 3], zlib[pos + 4]]);
+        assert_eq!(len, !nlen);
+        data.extend_from_slice(&zlib[pos + 5..pos + 5 + len as usize]);
+        pos += 5 + len as usize;
+        if header & 1 == 1 {
+            break;
+        }
+    }
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    assert_eq!(be_u32(&zlib[pos..]), (b << 16) | a, "adler32");
+    assert_eq!(pos + 4, zlib.len());
+    data
+}
+
+#[test]
+fn png_frame_decodes_to_the_picture() {
+    let replay = played("lab0.txt");
+    let png = png::frame(&replay, 0).to_png();
+
+    let chunks = chunks(&png);
+    let kinds: Vec<_> = chunks.iter().map(|(kind, _)| kind).collect();
+    assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+
+    let ihdr = &chunks[0].1;
+    let (width, height) = (be_u32(&ihdr[0..]) as usize, be_u32(&ihdr[4..]) as usize);
+    assert_eq!((width, height), (replay.width() * 8, replay.height() * 8));
+    assert_eq!(&ihdr[8..], [8, 2, 0, 0, 0], "8 bits RGB");
+
+    let raw = inflate_stored(&chunks[1].1);
+    assert_eq!(raw.len(), (width * 3 + 1) * height);
+    let pixel = |x: usize, y: usize| {
+        let idx = y * (width * 3 + 1) + 1 + x * 3;
+        render::Color(raw[idx], raw[idx + 1], raw[idx + 2])
+    };
+    assert!(
+        raw.chunks(width * 3 + 1).all(|row| row[0] == 0),
+        "no filter"
+    );
+
+    // Kirk at the centre of his starting square, the fog on the far corner never seen at the first turn.
+    let start = replay.start();
+    assert_eq!(pixel(start.x * 8 + 4, start.y * 8 + 4), render::KIRK);
+    assert_eq!(pixel(width - 1, height - 1), render::FOG);
+}
+
+#[test]
+fn png_of_an_empty_canvas_is_valid() {
+    let png = png::encode_png(0, 0, &[]);
+    let chunks = chunks(&png);
+    assert!(inflate_stored(&chunks[1].1).is_empty());
+}
+
+// Tags are balanced and attribute values are quoted, enough to be sure a viewer parses the picture.
+fn assert_well_formed(svg: &str) {
+    let mut open_tags = Vec::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        let end = start + rest[start..].find('>').expect("unclosed tag");
+        let tag = &rest[start + 1..end];
+        assert!(!tag.contains('<'), "tag {}", tag);
+        assert_eq!(
+            tag.matches('"').count() % 2,
+            0,
+            "unbalanced quotes in {}",
+            tag
+        );
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap();
+        if tag.starts_with('/') {
+            assert_eq!(open_tags.pop(), Some(name), "closing {}", tag);
+        } else if !tag.ends_with('/') {
+            open_tags.push(name);
+        }
+        rest = &rest[end + 1..];
+    }
+    assert!(open_tags.is_empty(), "unclosed {:?}", open_tags);
+    assert!(svg.trim_start().starts_with("<svg"));
+}
+
+#[test]
+fn svg_pictures_are_well_formed() {
+    let replay = played("lab0.txt");
+
+    assert_well_formed(&svg::map_svg(&replay));
+    assert_well_formed(&svg::trace_svg(&replay));
+    assert_well_formed(&svg::animated_trace_svg(&replay, 0.1));
+}
+
+#[test]
+fn heatmap_svg_is_well_formed() {
+    let replay = played("lab8.txt");
+    let stats = GameStats::from_replay(&replay);
+    let heatmap = svg::heatmap_svg(&replay, &stats);
+    assert_well_formed(&heatmap);
+    assert!(heatmap.contains(&format!("<title>{} visits</title>", stats.max_visits)));
+}
+
+#[test]
+fn hex_svg_draws_a_hexagon_per_square() {
+    let replay = played("lab_hex.txt");
+    let hex = svg::hex_svg(&replay);
+    assert_well_formed(&hex);
+    assert_eq!(
+        hex.matches("<polygon ").count(),
+        replay.width() * replay.height()
+    );
+}
+
+// Points of the polylines of an SVG picture.
+fn polylines(svg: &str) -> Vec<Vec<(f64, f64)>> {
+    svg.split(r#"<polyline points=""#)
+        .skip(1)
+        .map(|rest| {
+            rest[..rest.find('"').unwrap()]
+                .split(' ')
+                .map(|point| {
+                    let (x, y) = point.split_once(',').unwrap();
+                    (x.parse().unwrap(), y.parse().unwrap())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn trajectory_lines_stop_at_portals_and_wrapped_edges() {
+    for filename in &["lab_portals.txt", "lab_torus.txt"] {
+        let replay = played(filename);
+        let lines = polylines(&svg::trace_svg(&replay));
+        assert!(!lines.is_empty(), "{}", filename);
+        for line in lines {
+            for step in line.windows(2) {
+                let ((x1, y1), (x2, y2)) = (step[0], step[1]);
+                assert!(
+                    (x2 - x1).abs() <= 16. && (y2 - y1).abs() <= 16.,
+                    "{}: {:?}",
+                    filename,
+                    step
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn html_title_is_escaped() {
+    let page = html::replay_html(&played("lab0.txt"), r#"lab8 & <x> "y" 'z'"#);
+    let escaped = "lab8 &amp; &lt;x&gt; &quot;y&quot; &#39;z&#39;";
+    assert!(page.contains(&format!("<title>{}</title>", escaped)));
+    assert!(page.contains(&format!("<h1>{}</h1>", escaped)));
+}
+
+#[test]
+fn html_map_rows_are_escaped_and_coloured() {
+    let game_sample =
+        GameSample::from_sample_string("1 1 5\n#######\n#T.C\"\\#\n#r.R\u{1}~#\n#######").unwrap();
+    let page = html::replay_html(game_sample.replay(), "escaped");
+
+    assert!(page.contains(r##""#T.C\"\\#""##));
+    assert!(page.contains(r##""#r.R\u0001~#""##));
+    for cell in b"rR~?" {
+        let color = render::cell_color(*cell);
+        assert!(page.contains(&format!("\"{}\":\"{}\"", *cell as char, color)));
+    }
+    assert!(page.contains(&format!(r#""\"":"{}""#, render::cell_color(b'"'))));
+}
+
+#[test]
+fn torus_game_won_across_the_edge_is_exported() {
+    // the last move wraps from the control room to the starting point
+    let mut game_sample =
+        GameSample::from_sample_string("1 0 5 grid=torus\n#T#\n#.#\n#C#").unwrap();
+    for _ in 0..3 {
+        game_sample.round_data();
+        game_sample.set_action("DOWN");
+    }
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+
+    let replay = game_sample.replay();
+    assert_eq!(replay.trajectory().last(), Some(&replay.start()));
+    let stats = GameStats::from_replay(replay);
+    assert_eq!(stats.moves, 3);
+    assert_eq!(stats.return_length, Some(1));
+    assert_eq!(stats.optimal_return_length, Some(1));
+
+    render::ansi::heatmap(replay, &stats);
+    assert_well_formed(&svg::trace_svg(replay));
+    assert_well_formed(&svg::animated_trace_svg(replay, 0.1));
+    assert_well_formed(&svg::heatmap_svg(replay, &stats));
+    chunks(&png::frame(replay, replay.turns().len()).to_png());
+    assert!(html::replay_html(replay, "torus").contains("<title>torus</title>"));
+}
@@ -0,0 +1,118 @@
+// Play every labyrinth of the samples directory through the GameSample referee, and the labyrinths of several
+// explorers through the TeamGame referee.
+// Run with UPDATE_SNAPSHOTS=1 to accept new turn counts after a deliberate solver change.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use the_labyrinth::game_sample::{GameSample, Outcome, FUEL};
+use the_labyrinth::team_game::TeamGame;
+
+const SNAPSHOT_FILENAME: &str = "tests/snapshots/samples_turn_counts.txt";
+
+fn sample_filenames() -> Vec<PathBuf> {
+    let samples_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("samples");
+    let mut filenames: Vec<_> = fs::read_dir(samples_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    filenames.sort();
+    filenames
+}
+
+fn play(filename: &Path) -> GameSample {
+    let mut game_sample = GameSample::new(filename.to_str().unwrap()).unwrap();
+    game_sample.play().unwrap();
+    game_sample
+}
+
+// The samples with several explorers, played by a team.
+fn team_games() -> Vec<(PathBuf, TeamGame)> {
+    sample_filenames()
+        .into_iter()
+        .filter_map(|filename| {
+            let team_game = TeamGame::new(filename.to_str().unwrap()).unwrap();
+            (team_game.starts().len() > 1).then_some((filename, team_game))
+        })
+        .map(|(filename, mut team_game)| {
+            team_game.play();
+            (filename, team_game)
+        })
+        .collect()
+}
+
+#[test]
+fn every_sample_is_won_within_alarm_and_fuel() {
+    let filenames = sample_filenames();
+    assert!(!filenames.is_empty());
+
+    for filename in filenames {
+        let game_sample = play(&filename);
+        let replay = game_sample.replay();
+        let moves = replay.trajectory().len() - 1;
+
+        assert_eq!(game_sample.outcome(), Some(Outcome::Won), "{:?}", filename);
+        assert!(moves <= FUEL, "{:?}: {} moves", filename, moves);
+        assert_eq!(FUEL - game_sample.fuel_left(), moves, "{:?}", filename);
+
+        let alarm_turn = replay.alarm_turn().unwrap();
+        assert!(
+            moves - alarm_turn <= replay.rounds(),
+            "{:?}: return in {} moves, alarm after {} rounds",
+            filename,
+            moves - alarm_turn,
+            replay.rounds()
+        );
+    }
+
+    let team_games = team_games();
+    assert!(!team_games.is_empty());
+    for (filename, team_game) in team_games {
+        assert_eq!(team_game.outcome(), Some(Outcome::Won), "{:?}", filename);
+        let alarm_turn = team_game.alarm_turn().unwrap();
+        assert!(team_game.moves() - alarm_turn <= team_game.settings().rounds);
+    }
+}
+
+#[test]
+fn turn_counts_match_snapshot() {
+    let mut snapshot = String::from("# sample moves alarm_turn return_moves\n");
+    for filename in sample_filenames() {
+        let game_sample = play(&filename);
+        let replay = game_sample.replay();
+        let moves = replay.trajectory().len() - 1;
+        let alarm_turn = replay.alarm_turn().unwrap();
+        snapshot.push_str(&format!(
+            "{} {} {} {}\n",
+            filename.file_name().unwrap().to_str().unwrap(),
+            moves,
+            alarm_turn,
+            moves - alarm_turn
+        ));
+    }
+    for (filename, team_game) in team_games() {
+        let alarm_turn = team_game.alarm_turn().unwrap();
+        snapshot.push_str(&format!(
+            "{} team of {} {} {} {}\n",
+            filename.file_name().unwrap().to_str().unwrap(),
+            team_game.starts().len(),
+            team_game.moves(),
+            alarm_turn,
+            team_game.moves() - alarm_turn
+        ));
+    }
+
+    let snapshot_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT_FILENAME);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(snapshot_path, &snapshot).unwrap();
+    } else {
+        let expected = fs::read_to_string(snapshot_path).unwrap_or_default();
+        assert_eq!(
+            expected, snapshot,
+            "turn counts changed, run with UPDATE_SNAPSHOTS=1 to accept them"
+        );
+    }
+}
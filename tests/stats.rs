@@ -0,0 +1,201 @@
+// Statistics of recorded games, on small hand-made labyrinths.
+
+use the_labyrinth::grid::Grid;
+use the_labyrinth::keys::{KeyColor, KeySet};
+use the_labyrinth::movement::Movement;
+use the_labyrinth::replay::Replay;
+use the_labyrinth::stats::{shortest_distance, GameStats};
+use the_labyrinth::topology::Torus;
+use the_labyrinth::vect2_t::UCoord2;
+
+fn map(rows: &[&str]) -> Grid<u8> {
+    Grid::from_rows(rows.iter().map(|row| row.as_bytes().to_vec()).collect())
+}
+
+// A corridor, T on the left and C on the right.
+const CORRIDOR: &[&str] = &["#######", "#T...C#", "#######"];
+
+// Replay of Kirk walking the squares of the trajectory, the radar revealing the given columns at each turn and the
+// solver backtracking at the given turns.
+fn replay(
+    rows: &[&str],
+    trajectory: &[(usize, usize)],
+    revealed_columns: &[(usize, usize)],
+    backtracking_turns: &[usize],
+) -> Replay {
+    let map = map(rows);
+    let height = map.height();
+    let trajectory: Vec<UCoord2> = trajectory
+        .iter()
+        .map(|&(x, y)| UCoord2::new(x, y))
+        .collect();
+    let mut replay = Replay::new(map, trajectory[0], 20);
+    for (turn, step) in trajectory.windows(2).enumerate() {
+        let revealed = revealed_columns
+            .iter()
+            .filter(|&&(at_turn, _)| at_turn == turn)
+            .flat_map(|&(_, x)| (0..height).map(move |y| UCoord2::new(x, y)))
+            .collect();
+        replay.push_turn(step[0], revealed);
+        replay.set_last_action(step[1] - step[0]);
+        replay.set_last_decision(Vec::new(), backtracking_turns.contains(&turn));
+    }
+    replay
+}
+
+#[test]
+fn visits_count_every_square_stood_on() {
+    let trajectory = [
+        (1, 1),
+        (2, 1),
+        (3, 1),
+        (2, 1),
+        (3, 1),
+        (4, 1),
+        (5, 1),
+        (4, 1),
+        (3, 1),
+        (2, 1),
+        (1, 1),
+    ];
+    let stats = GameStats::from_replay(&replay(CORRIDOR, &trajectory, &[], &[]));
+
+    assert_eq!(stats.moves, 10);
+    assert_eq!(stats.visits[1][1..6], [2, 3, 3, 2, 1]);
+    assert_eq!(stats.squares_visited, 5);
+    assert_eq!(stats.revisits, 1 + 2 + 2 + 1);
+    assert_eq!(stats.max_visits, 3);
+}
+
+#[test]
+fn backtracking_steps_are_the_turns_the_solver_backtracked() {
+    let trajectory = [(1, 1), (2, 1), (3, 1), (2, 1), (3, 1), (4, 1), (5, 1)];
+    let stats = GameStats::from_replay(&replay(CORRIDOR, &trajectory, &[], &[2, 3]));
+    assert_eq!(stats.backtracking_steps, 2);
+}
+
+#[test]
+fn map_revealed_when_the_control_room_is_found() {
+    let trajectory = [(1, 1), (2, 1), (3, 1), (4, 1), (5, 1)];
+    let revealed_columns = [(0, 0), (0, 1), (0, 2), (0, 3), (1, 4), (2, 5), (3, 6)];
+    let stats = GameStats::from_replay(&replay(CORRIDOR, &trajectory, &revealed_columns, &[]));
+
+    assert_eq!(stats.cmd_room_found_turn, Some(2));
+    // 6 columns of 3 squares out of 21
+    let revealed = stats.revealed_when_cmd_room_found.unwrap();
+    assert!((revealed - 100. * 18. / 21.).abs() < 1e-9, "{}", revealed);
+    assert_eq!(stats.alarm_turn, Some(4));
+}
+
+#[test]
+fn control_rooms_are_found_when_enough_of_them_are_revealed() {
+    let rows = ["#######", "#C.T.C#", "#######"];
+    let trajectory = [(3, 1), (2, 1), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1)];
+    let mut replay = replay(&rows, &trajectory, &[(0, 1), (4, 5)], &[]);
+    assert_eq!(GameStats::from_replay(&replay).cmd_room_found_turn, Some(0));
+
+    replay.set_cmd_rooms_to_visit(2);
+    let stats = GameStats::from_replay(&replay);
+    assert_eq!(stats.cmd_room_found_turn, Some(4));
+    assert_eq!(stats.alarm_turn, Some(6));
+}
+
+#[test]
+fn return_ratio_compares_the_return_to_the_shortest_one() {
+    let trajectory = [
+        (1, 1),
+        (2, 1),
+        (3, 1),
+        (4, 1),
+        (5, 1),
+        (4, 1),
+        (3, 1),
+        (4, 1),
+        (3, 1),
+        (2, 1),
+        (1, 1),
+    ];
+    let stats = GameStats::from_replay(&replay(CORRIDOR, &trajectory, &[], &[]));
+
+    assert_eq!(stats.alarm_turn, Some(4));
+    assert_eq!(stats.return_length, Some(6));
+    assert_eq!(stats.optimal_return_length, Some(4));
+    assert_eq!(stats.return_ratio(), Some(1.5));
+}
+
+#[test]
+fn no_return_without_alarm() {
+    let trajectory = [(1, 1), (2, 1), (3, 1)];
+    let stats = GameStats::from_replay(&replay(CORRIDOR, &trajectory, &[], &[]));
+
+    assert_eq!(stats.alarm_turn, None);
+    assert_eq!(stats.return_length, None);
+    assert_eq!(stats.return_ratio(), None);
+}
+
+#[test]
+fn distance_follows_the_movement_model() {
+    let map = map(&["#####", "#...#", "#...#", "#...#", "#####"]);
+    let (from, to) = (UCoord2::new(1, 1), UCoord2::new(3, 3));
+
+    let distance = |movement: Movement| shortest_distance(&map, &movement, from, KeySet::new(), to);
+    assert_eq!(distance(Movement::orthogonal()), Some(4));
+    assert_eq!(distance(Movement::eight_directions(false)), Some(2));
+}
+
+#[test]
+fn distance_wraps_around_a_torus() {
+    let map = map(&[".#..."]);
+    let (from, to) = (UCoord2::new(0, 0), UCoord2::new(4, 0));
+
+    assert_eq!(
+        shortest_distance(&map, &Movement::orthogonal(), from, KeySet::new(), to),
+        None
+    );
+    let torus = Torus::new(Movement::orthogonal(), 5, 1);
+    assert_eq!(
+        shortest_distance(&map, &torus, from, KeySet::new(), to),
+        Some(1)
+    );
+}
+
+#[test]
+fn distance_walks_around_hazards() {
+    let map = map(&["#####", "#.~.#", "#...#", "#####"]);
+    assert_eq!(
+        shortest_distance(
+            &map,
+            &Movement::orthogonal(),
+            UCoord2::new(1, 1),
+            KeySet::new(),
+            UCoord2::new(3, 1)
+        ),
+        Some(4)
+    );
+}
+
+#[test]
+fn distance_opens_doors_with_the_keys_held_or_picked_up() {
+    let map = map(&["#######", "#..R..#", "#r#####", "#######"]);
+    let (from, to) = (UCoord2::new(1, 1), UCoord2::new(5, 1));
+    let distance = |keys| shortest_distance(&map, &Movement::orthogonal(), from, keys, to);
+
+    // the key is fetched before the door
+    assert_eq!(distance(KeySet::new()), Some(6));
+    assert_eq!(distance(KeySet::new().with(KeyColor::Red)), Some(4));
+}
+
+#[test]
+fn distance_crosses_portals() {
+    let map = map(&["#######", "#.1#1.#", "#######"]);
+    assert_eq!(
+        shortest_distance(
+            &map,
+            &Movement::orthogonal(),
+            UCoord2::new(1, 1),
+            KeySet::new(),
+            UCoord2::new(5, 1)
+        ),
+        Some(2)
+    );
+}
@@ -0,0 +1,94 @@
+// Several explorers sharing one board.
+
+use the_labyrinth::board::Board;
+use the_labyrinth::exploration;
+use the_labyrinth::game_sample::{GameSample, Outcome, FUEL};
+use the_labyrinth::team_game::TeamGame;
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+const LAB5: &str = include_str!("../samples/lab5.txt");
+
+fn with_header(header: &str) -> String {
+    let map: Vec<&str> = LAB5.lines().skip(1).collect();
+    format!("{}\n{}", header, map.join("\n"))
+}
+
+#[test]
+fn board_merges_the_views_of_several_explorers() {
+    let mut board = Board::new(9, 1, 10);
+    board.begin_update();
+    board.merge_view(UCoord2::new(1, 0), &[".K..?????".to_owned()]);
+    board.merge_view(UCoord2::new(7, 0), &["?????#.K.".to_owned()]);
+
+    assert_eq!(board.to_string().trim(), ".?..?#.?.");
+    assert_eq!(board.last_seen(&UCoord2::new(0, 0)), Some(1));
+    assert_eq!(board.last_seen(&UCoord2::new(8, 0)), Some(1));
+}
+
+#[test]
+fn explorers_on_the_same_square_take_different_ways() {
+    let mut board = Board::new(11, 3, 10);
+    let rows: Vec<String> = ["###########", "#.........#", "###########"]
+        .iter()
+        .map(|row| row.to_string())
+        .collect();
+    let explorer = UCoord2::new(5, 1);
+    board.update_with(explorer, &rows);
+
+    let targets = exploration::assign_frontier_targets(&board, &[explorer, explorer]);
+    assert_eq!(
+        targets,
+        vec![Some(UCoord2::new(3, 1)), Some(UCoord2::new(7, 1))]
+    );
+}
+
+#[test]
+fn explorers_start_from_the_header_and_the_other_start_squares() {
+    let team_game =
+        TeamGame::from_sample_string("1 1 10 agents=2\n#####\n#...#\n#..T#\n#####").unwrap();
+    assert_eq!(
+        team_game.starts(),
+        vec![UCoord2::new(1, 1), UCoord2::new(1, 1), UCoord2::new(3, 2)]
+    );
+}
+
+#[test]
+fn team_wins_in_fewer_moves_than_kirk_alone() {
+    let mut game_sample = GameSample::from_sample_string(LAB5).unwrap();
+    game_sample.play().unwrap();
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+    let alone = FUEL - game_sample.fuel_left();
+
+    let mut team_game = TeamGame::from_sample_string(&with_header("20 1 42 agents=3")).unwrap();
+    let team = team_game.play();
+    assert_eq!(team_game.outcome(), Some(Outcome::Won));
+    assert!(team.is_returning());
+    assert!(team_game.moves() < alone, "{} {}", team_game.moves(), alone);
+}
+
+#[test]
+fn explorers_share_the_toggling_walls() {
+    // the wall below the second explorer opens after the first round, though only the first explorer moved
+    let mut team_game =
+        TeamGame::from_sample_string("1 1 10 toggle=1\n#####\n#T..#\n#T%.#\n#####").unwrap();
+    let (right, left) = (UDelta2::new(1, 0), UDelta2::new(-1, 0));
+
+    team_game.set_actions(&[Some(right), None]);
+    assert_eq!(team_game.world().map()[UCoord2::new(2, 2)], b'.');
+    team_game.set_actions(&[Some(left), Some(right)]);
+    assert_eq!(team_game.outcome(), None);
+    assert_eq!(
+        team_game.coords(),
+        vec![UCoord2::new(1, 1), UCoord2::new(2, 2)]
+    );
+}
+
+#[test]
+fn guards_step_once_a_round() {
+    let mut team_game =
+        TeamGame::from_sample_string("1 1 10\n#######\n#T....#\n#T...<#\n#######").unwrap();
+    let right = UDelta2::new(1, 0);
+
+    team_game.set_actions(&[Some(right), Some(right)]);
+    assert_eq!(team_game.world().guards()[0].coord(), UCoord2::new(4, 2));
+}
@@ -0,0 +1,105 @@
+// Labyrinths wrapping around their edges.
+
+use std::sync::Arc;
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::{Board, Content};
+use the_labyrinth::direction::Direction;
+use the_labyrinth::game_sample::{GameSample, Outcome};
+use the_labyrinth::movement::Movement;
+use the_labyrinth::range_helper::wrapped_range;
+use the_labyrinth::topology::{Topology, Torus};
+use the_labyrinth::vect2_t::{UCoord2, UDelta2};
+
+fn torus_board(rows: &[&str]) -> Board {
+    let rows: Vec<String> = rows.iter().map(|row| row.to_string()).collect();
+    let mut board = Board::from_rows(&rows, 0);
+    let (width, height) = (board.width(), board.height());
+    board.set_topology(Arc::new(Torus::new(Movement::orthogonal(), width, height)));
+    board
+}
+
+#[test]
+fn radar_window_wraps() {
+    assert_eq!(wrapped_range(0, 10), vec![0, 1, 2, 8, 9]);
+    assert_eq!(wrapped_range(9, 10), vec![0, 1, 7, 8, 9]);
+    assert_eq!(wrapped_range(1, 3), vec![0, 1, 2]);
+}
+
+#[test]
+fn moves_and_distances_wrap() {
+    let torus = Torus::new(Movement::orthogonal(), 10, 6);
+    assert_eq!(
+        torus.neighbour(UCoord2::new(0, 0), UDelta2::new(-1, 0), 10, 6),
+        Some(UCoord2::new(9, 0))
+    );
+    assert_eq!(
+        torus.neighbour(UCoord2::new(3, 5), UDelta2::new(0, 1), 10, 6),
+        Some(UCoord2::new(3, 0))
+    );
+    assert_eq!(
+        torus.wrapped_delta(UCoord2::new(1, 1), UCoord2::new(8, 4)),
+        UDelta2::new(-3, 3)
+    );
+    assert_eq!(torus.distance(UCoord2::new(1, 1), UCoord2::new(8, 4)), 6);
+
+    // the square grid keeps its edges
+    let movement = Movement::orthogonal();
+    assert_eq!(
+        movement.neighbour(UCoord2::new(0, 0), UDelta2::new(-1, 0), 10, 6),
+        None
+    );
+}
+
+#[test]
+fn paths_cross_the_edges() {
+    let board = torus_board(&["..#..", "..#..", "..#.."]);
+    assert!(board
+        .neighbours_in_board_iter(UCoord2::new(0, 0), Direction::Left)
+        .any(|coord| coord == UCoord2::new(4, 0)));
+
+    let path = AStarAlgo::new().compute_path(
+        &board,
+        UCoord2::new(1, 1),
+        UCoord2::new(3, 1),
+        &[Content::Empty],
+    );
+    assert_eq!(
+        path,
+        vec![UCoord2::new(0, 1), UCoord2::new(4, 1), UCoord2::new(3, 1)]
+    );
+    assert_eq!(
+        board.step_to(UCoord2::new(0, 1), UCoord2::new(4, 1)),
+        UDelta2::new(-1, 0)
+    );
+}
+
+#[test]
+fn referee_wraps_moves_and_radar() {
+    let map = "..........\n..........\n..........\n..........\n....#....C\n..........";
+
+    let mut game_sample =
+        GameSample::from_sample_string(&format!("0 0 10 grid=torus\n{}", map)).unwrap();
+    let data = game_sample.round_data();
+    // the window around the corner shows the last rows and columns
+    let rows: Vec<&str> = data.lines().skip(1).collect();
+    assert_eq!(rows[0], "K..?????..");
+    assert_eq!(rows[3], "??????????");
+    assert_eq!(rows[4], "...?????.C");
+    game_sample.set_action("UP");
+    assert_eq!(game_sample.outcome(), None);
+    assert_eq!(game_sample.current(), UCoord2::new(0, 5));
+
+    // without the torus the border stops Kirk
+    let mut game_sample = GameSample::from_sample_string(&format!("0 0 10\n{}", map)).unwrap();
+    game_sample.round_data();
+    game_sample.set_action("UP");
+    assert_eq!(game_sample.outcome(), Some(Outcome::HitWall));
+}
+
+#[test]
+fn torus_sample_is_won() {
+    let mut game_sample = GameSample::new("samples/lab_torus.txt").unwrap();
+    game_sample.play().unwrap();
+    assert_eq!(game_sample.outcome(), Some(Outcome::Won));
+}
@@ -0,0 +1,94 @@
+// Ordering of the control rooms in the multiple rooms variant.
+
+use the_labyrinth::a_star_algo::AStarAlgo;
+use the_labyrinth::board::Board;
+use the_labyrinth::protocol::{self, GameSettings};
+use the_labyrinth::tour::best_tour;
+use the_labyrinth::vect2_t::UCoord2;
+
+// A corridor along y = 1, Kirk at x = 1.
+fn corridor(width: usize) -> Board {
+    let rows: Vec<String> = (0..3)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    if y == 1 && x > 0 && x < width - 1 {
+                        '.'
+                    } else {
+                        '#'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    Board::from_rows(&rows, 0)
+}
+
+#[test]
+fn rooms_are_visited_along_the_way() {
+    let board = corridor(20);
+    let rooms = [UCoord2::new(15, 1), UCoord2::new(5, 1), UCoord2::new(10, 1)];
+
+    let tour = best_tour(
+        &mut AStarAlgo::new(),
+        &board,
+        UCoord2::new(1, 1),
+        &rooms,
+        3,
+        None,
+    );
+
+    assert_eq!(
+        tour,
+        vec![UCoord2::new(5, 1), UCoord2::new(10, 1), UCoord2::new(15, 1)]
+    );
+}
+
+#[test]
+fn last_room_is_the_closest_to_the_end() {
+    let board = corridor(20);
+    let start = UCoord2::new(10, 1);
+    let rooms = [UCoord2::new(2, 1), UCoord2::new(17, 1)];
+
+    // going to 17 first would need 7 + 15 + 1 moves, going to 2 first 8 + 15 + 16
+    let tour = best_tour(
+        &mut AStarAlgo::new(),
+        &board,
+        start,
+        &rooms,
+        2,
+        Some(UCoord2::new(1, 1)),
+    );
+
+    assert_eq!(tour, vec![UCoord2::new(17, 1), UCoord2::new(2, 1)]);
+}
+
+#[test]
+fn only_the_closest_rooms_are_kept_when_some_are_enough() {
+    let board = corridor(20);
+    let rooms = [UCoord2::new(18, 1), UCoord2::new(3, 1), UCoord2::new(6, 1)];
+
+    let tour = best_tour(
+        &mut AStarAlgo::new(),
+        &board,
+        UCoord2::new(1, 1),
+        &rooms,
+        2,
+        None,
+    );
+
+    assert_eq!(tour, vec![UCoord2::new(3, 1), UCoord2::new(6, 1)]);
+}
+
+#[test]
+fn init_line_tells_the_number_of_rooms() {
+    let settings = protocol::parse_init_line("15 30 60 3\n").unwrap();
+    assert_eq!(settings.cmd_rooms, 3);
+    assert_eq!(settings.to_string(), "15 30 60 3");
+
+    let classic = protocol::parse_init_line("15 30 60\n").unwrap();
+    assert_eq!(classic, GameSettings::new(15, 30, 60));
+    assert_eq!(classic.to_string(), "15 30 60");
+
+    assert!(protocol::parse_init_line("15 30 60 0\n").is_err());
+}